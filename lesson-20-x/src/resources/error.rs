@@ -10,6 +10,8 @@ pub enum Error {
     FileContainsNil,
     #[fail(display = "Failed get executable path")]
     FailedToGetExePath,
+    #[fail(display = "Resource path {} leads outside of the root", name)]
+    PathOutsideRoot { name: String },
     #[fail(display = "Failed to load image {}", name)]
     FailedToLoadImage { name: String, #[cause] inner: image::ImageError },
     #[fail(display = "Image {} is not RGBA", name)]
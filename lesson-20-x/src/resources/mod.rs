@@ -1,4 +1,4 @@
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, Component};
 use std::fs;
 use std::io::Read;
 use std::ffi;
@@ -30,8 +30,13 @@ impl Resources {
         let exe_path = exe_file_name.parent()
             .ok_or(Error::FailedToGetExePath)?;
 
+        let mut root_path: PathBuf = exe_path.into();
+        for part in rel_path.as_ref().items() {
+            root_path = root_path.join(part);
+        }
+
         Ok(Resources {
-            root_path: resource_name_to_path(&exe_path, rel_path.as_ref())
+            root_path
         })
     }
 
@@ -41,7 +46,7 @@ impl Resources {
 
     pub fn load_cstring<P: AsRef<ResourcePath>>(&self, rel_path: P) -> Result<ffi::CString, Error> {
         let mut file = fs::File::open(
-            resource_name_to_path(&self.root_path, rel_path.as_ref())
+            self.path_for(rel_path.as_ref())?
         )?;
 
         // allocate buffer of the same size as file
@@ -60,7 +65,7 @@ impl Resources {
 
     pub fn load_rgb_image<P: AsRef<ResourcePath>>(&self, rel_path: P) -> Result<image::RgbImage, Error> {
         let img = image::open(
-            resource_name_to_path(&self.root_path, rel_path.as_ref())
+            self.path_for(rel_path.as_ref())?
         ).map_err(|e| Error::FailedToLoadImage { name: rel_path.as_ref().to_string(), inner: e })?;
 
         Ok(img.to_rgb())
@@ -68,7 +73,7 @@ impl Resources {
 
     pub fn load_rgba_image<P: AsRef<ResourcePath>>(&self, rel_path: P) -> Result<image::RgbaImage, Error> {
         let img = image::open(
-            resource_name_to_path(&self.root_path, rel_path.as_ref())
+            self.path_for(rel_path.as_ref())?
         ).map_err(|e| Error::FailedToLoadImage { name: rel_path.as_ref().to_string(), inner: e })?;
 
         if let image::ColorType::RGBA(_) = img.color() {
@@ -82,14 +87,26 @@ impl Resources {
         obj::ModelsWithMaterials::load(&self.root_path, rel_path.as_ref())
             .map_err(|e| Error::FailedToLoadObj { name: rel_path.as_ref().to_string(), inner: e })
     }
+
+    fn path_for(&self, rel_path: &ResourcePath) -> Result<PathBuf, Error> {
+        resource_name_to_path(&self.root_path, rel_path)
+            .ok_or_else(|| Error::PathOutsideRoot { name: rel_path.to_string() })
+    }
 }
 
-fn resource_name_to_path(root_dir: &Path, location: &ResourcePath) -> PathBuf {
+/// Returns `None` if the location leads outside of the root directory.
+fn resource_name_to_path(root_dir: &Path, location: &ResourcePath) -> Option<PathBuf> {
+    let location = location.normalize()?;
     let mut path: PathBuf = root_dir.into();
 
-    for part in location.items() {
-        path = path.join(part);
+    for part in location.items().filter(|part| !part.is_empty()) {
+        // on Windows, items like "C:" would replace the root
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => path.push(part),
+            _ => return None,
+        }
     }
 
-    path
+    Some(path)
 }
\ No newline at end of file
@@ -9,6 +9,8 @@ pub enum Error {
     LoadError(#[cause] tobj::LoadError),
     #[fail(display = "Resource path must not be empty")]
     ResourcePathMustNotBeEmpty,
+    #[fail(display = "Resource path leads outside of the root")]
+    ResourcePathOutsideRoot,
 }
 
 impl From<tobj::LoadError> for Error {
@@ -24,7 +26,8 @@ pub struct ModelsWithMaterials {
 
 impl ModelsWithMaterials {
     pub fn load(root_path: &Path, resource_path: &ResourcePath) -> Result<mesh::MeshSet, Error> {
-        let fs_path = super::resource_name_to_path(root_path, resource_path);
+        let fs_path = super::resource_name_to_path(root_path, resource_path)
+            .ok_or(Error::ResourcePathOutsideRoot)?;
         let resource_dir = resource_path.parent().ok_or(Error::ResourcePathMustNotBeEmpty)?;
 
         let (models, materials) = tobj::load_obj(&fs_path)?;
@@ -85,16 +85,69 @@ impl ResourcePath {
         self.inner.split('/')
     }
 
-    /// Returns path as str and ensures that the returned str does not have a leading or trailing slash
+    /// Returns path as str and ensures that the returned str does not have leading or trailing slashes
     pub fn as_clean_str(&self) -> &str {
-        let mut result = &self.inner;
-        if result.starts_with('/') {
-            result = &result[1..];
+        self.inner.trim_matches('/')
+    }
+
+    /// Returns the path with `\` separators replaced by `/`, empty and `.` items removed,
+    /// and `..` items applied to the previous item.
+    ///
+    /// Returns `None` if `..` items lead outside of the root.
+    pub fn normalize(&self) -> Option<ResourcePathBuf> {
+        let mut items = Vec::new();
+        for item in self.inner.split(|c| c == '/' || c == '\\') {
+            match item {
+                "" | "." => (),
+                ".." => {
+                    items.pop()?;
+                },
+                item => items.push(item),
+            }
+        }
+
+        Some(ResourcePathBuf {
+            inner: items.join("/")
+        })
+    }
+
+    /// Returns the last item of the path, or `None` if the path is empty or ends with `..`.
+    pub fn file_name(&self) -> Option<&str> {
+        self.as_clean_str().rsplit('/').next()
+            .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+    }
+
+    /// Returns the part of the file name after the last `.`.
+    ///
+    /// File names that start with `.` and have no other `.` have no extension.
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name()?;
+        match name.rfind('.') {
+            None | Some(0) => None,
+            Some(index) => Some(&name[index + 1..]),
         }
-        if result.ends_with('/') {
-            result = &result[..1];
+    }
+
+    /// Returns the path with the extension of the file name replaced,
+    /// or removed if the `extension` is empty.
+    pub fn with_extension(&self, extension: &str) -> ResourcePathBuf {
+        let path = self.as_clean_str();
+        if self.file_name().is_none() {
+            return ResourcePathBuf::from(path.as_ref());
+        }
+
+        let stem = match self.extension() {
+            Some(current) => &path[..path.len() - current.len() - 1],
+            None => path,
+        };
+
+        if extension.is_empty() {
+            ResourcePathBuf::from(stem.as_ref())
+        } else {
+            ResourcePathBuf {
+                inner: [stem, ".", extension].concat()
+            }
         }
-        result
     }
 
     pub fn join<P: AsRef<ResourcePath>>(&self, other: P) -> ResourcePathBuf {
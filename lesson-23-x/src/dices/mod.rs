@@ -3,6 +3,7 @@ use failure;
 use render_gl::{self, DebugLines};
 use selection::{self, Selectables, SelectableAABB};
 use resources::Resources;
+use mesh::MeshSet;
 use nalgebra as na;
 
 mod buffers;
@@ -30,7 +31,7 @@ impl Dice {
         let p_material = dice_material::Material::load_for(&program);
 
         // this loader does not support file names with spaces
-        let imported_models = res.load_obj("objs/dice.obj")?;
+        let imported_models = (*res.load::<MeshSet, _>("objs/dice.obj")?.get()?).clone();
 
         // take first material in obj
         let material = imported_models.materials.into_iter().next();
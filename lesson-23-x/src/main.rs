@@ -1,5 +1,6 @@
 extern crate sdl2;
 extern crate gl;
+#[macro_use] extern crate resources;
 extern crate vec_2_10_10_10;
 extern crate half;
 extern crate slab;
@@ -15,7 +16,6 @@ extern crate once_cell;
 pub mod camera;
 pub mod render_gl;
 pub mod render;
-pub mod resources;
 pub mod mesh;
 pub mod selection;
 pub mod dices;
@@ -24,6 +24,7 @@ mod debug;
 
 use failure::err_msg;
 use resources::Resources;
+use resources::backend::{InMemory, FileSystem};
 use nalgebra as na;
 use std::time::{Instant, Duration};
 use floating_duration::TimeAsFloat;
@@ -42,7 +43,10 @@ fn main() {
 fn run() -> Result<(), failure::Error> {
     PeekAlloc::init();
 
-    let res = Resources::from_relative_exe_path("assets-23-x").unwrap();
+    // assets are embedded into the executable, and files on disk override them during development
+    let res = Resources::new()
+        .loaded_from("embedded", 0, InMemory::from_embedded(embedded_resources!("assets.rs")))
+        .loaded_from("assets", 1, FileSystem::from_rel_path(env!("CARGO_MANIFEST_DIR"), "assets"));
 
     let sdl = sdl2::init().map_err(err_msg)?;
     let video_subsystem = sdl.video().map_err(err_msg)?;
@@ -124,6 +128,7 @@ fn run() -> Result<(), failure::Error> {
     // main loop
 
     let mut time = Instant::now();
+    let mut print_gl_calls = false;
 
     let mut event_pump = sdl.event_pump().map_err(err_msg)?;
     'main: loop {
@@ -153,6 +158,9 @@ fn run() -> Result<(), failure::Error> {
                     allocation_profiler.toggle();
                     gl_call_profiler.toggle();
                 },
+                sdl2::event::Event::KeyDown { scancode: Some(sdl2::keyboard::Scancode::G), .. } => {
+                    print_gl_calls = true;
+                },
                 _ => (),
             }
         }
@@ -238,6 +246,17 @@ fn run() -> Result<(), failure::Error> {
             gl_call_profiler.push(gl_calls, render::color_light_blue());
         }
 
+        let gl_saved_calls = gl_watch::saved_calls();
+        if gl_saved_calls > 0 {
+            gl_call_profiler.push(gl_saved_calls, render::color_magenta());
+        }
+
+        // show which calls dominate the frame when asked with G
+        if print_gl_calls {
+            gl_watch::print_top_calls(5);
+            print_gl_calls = false;
+        }
+
         window.gl_swap_window();
     }
 
@@ -2,6 +2,8 @@ use nalgebra as na;
 use ncollide3d::bounding_volume::aabb::AABB;
 use resources::ResourcePathBuf;
 
+mod obj;
+
 #[derive(Clone, Debug)]
 pub struct MeshSet {
     pub meshes: Vec<Mesh>,
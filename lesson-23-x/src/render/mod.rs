@@ -32,6 +32,10 @@ pub fn color_gray() -> Vector3<f32> {
     Vector3::<f32>::new(0.5, 0.5, 0.5)
 }
 
+pub fn color_magenta() -> Vector3<f32> {
+    Vector3::<f32>::new(1.0, 0.0, 1.0)
+}
+
 pub struct WindowSize {
     pub width: i32,
     pub height: i32,
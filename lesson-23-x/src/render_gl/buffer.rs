@@ -38,7 +38,14 @@ impl Buffer where {
         }
     }
 
+    /// Binds no buffer to the target.
+    ///
+    /// With the `gl_state_cache` feature this does nothing: the buffer stays bound until another
+    /// one is bound, and the state cache skips binding it again.
     pub fn unbind(&self) {
+        if cfg!(feature = "gl_state_cache") {
+            return;
+        }
         unsafe {
             self.gl.BindBuffer(self.buffer_type, 0);
         }
@@ -183,4 +190,49 @@ impl Drop for VertexArray {
             self.gl.DeleteVertexArrays(1, &mut self.vao);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "gl_mock"))]
+mod test {
+    use gl::mock::{Mock, Object};
+    use super::{Buffer, VertexArray};
+
+    #[test]
+    fn buffer_should_be_deleted_on_drop() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        let buffer = Buffer::new_array(&gl);
+        assert_eq!(vec![(Object::Buffer, 1)], mock.live_objects());
+
+        drop(buffer);
+        assert!(mock.live_objects().is_empty());
+        assert_eq!(1, mock.calls_to("DeleteBuffers").len());
+    }
+
+    #[test]
+    #[cfg(feature = "gl_state_cache")]
+    fn binding_again_after_unbind_should_be_skipped_with_state_cache() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        let buffer = Buffer::new_array(&gl);
+        for _ in 0..3 {
+            buffer.bind();
+            buffer.unbind();
+        }
+        assert_eq!(1, mock.calls_to("BindBuffer").len());
+    }
+
+    #[test]
+    fn vertex_array_should_be_deleted_on_drop() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        let vao = VertexArray::new(&gl);
+        assert_eq!(vec![(Object::VertexArray, 1)], mock.live_objects());
+
+        drop(vao);
+        assert!(mock.live_objects().is_empty());
+    }
+}
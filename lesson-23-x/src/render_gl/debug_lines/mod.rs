@@ -379,4 +379,33 @@ fn render_p3(v: na::Point3<f32>) -> data::f32_f32_f32 {
 
 fn render_color_vec4(v: na::Vector4<f32>) -> data::u2_u10_u10_u10_rev_float {
     (v.x, v.y, v.z, v.w).into()
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "gl_mock"))]
+mod test {
+    use gl::mock::Mock;
+    use nalgebra as na;
+    use resources::Resources;
+    use resources::backend::InMemory;
+    use super::DebugLines;
+
+    #[test]
+    fn program_and_buffers_should_be_deleted_on_drop() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        let res = Resources::new()
+            .loaded_from("test", 0, InMemory::new()
+                .with("shaders/render_gl/debug_lines.vert", b"void main() {}")
+                .with("shaders/render_gl/debug_lines.frag", b"void main() {}"));
+
+        {
+            let mut lines = DebugLines::new(&gl, &res).unwrap();
+            let _marker = lines.marker(na::Point3::origin(), 1.0);
+            lines.check_if_invalidated_and_reinitialize(&gl);
+            assert!(lines.buffers.is_some());
+        }
+
+        assert!(mock.live_objects().is_empty());
+        assert!(mock.invalid_deletes().is_empty());
+    }
+}
@@ -71,6 +71,7 @@ impl Program {
                     std::ptr::null_mut(),
                     error.as_ptr() as *mut gl::types::GLchar
                 );
+                gl.DeleteProgram(program_id);
             }
 
             return Err(error.to_string_lossy().into_owned());
@@ -157,7 +158,8 @@ impl Shader {
             .map(|&(_, kind)| kind)
             .ok_or_else(|| Error::CanNotDetermineShaderTypeForResource { name: name.into() })?;
 
-        let source = res.load_cstring(name)
+        let source = res.load::<CString, _>(name)
+            .and_then(|source| source.get())
             .map_err(|e| Error::ResourceLoad { name: name.into(), inner: e })?;
 
         Shader::from_source(gl, &source, shader_kind)
@@ -225,6 +227,7 @@ fn shader_from_source(
                 std::ptr::null_mut(),
                 error.as_ptr() as *mut gl::types::GLchar
             );
+            gl.DeleteShader(id);
         }
 
         return Err(error.to_string_lossy().into_owned());
@@ -240,4 +243,55 @@ fn create_whitespace_cstring_with_len(len: usize) -> CString {
     buffer.extend([b' '].iter().cycle().take(len));
     // convert buffer to CString
     unsafe { CString::from_vec_unchecked(buffer) }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "gl_mock"))]
+mod test {
+    use gl;
+    use gl::mock::Mock;
+    use super::{Program, Shader};
+    use std::ffi::CString;
+
+    fn vert_source() -> CString {
+        CString::new("void main() {}").unwrap()
+    }
+
+    #[test]
+    fn program_and_shaders_should_be_deleted_on_drop() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        {
+            let shader = Shader::from_vert_source(&gl, &vert_source()).unwrap();
+            let _program = Program::from_shaders(&gl, &[shader]).unwrap();
+        }
+
+        assert!(mock.live_objects().is_empty());
+        assert!(mock.invalid_deletes().is_empty());
+    }
+
+    #[test]
+    fn compile_error_should_return_log_and_delete_shader() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        mock.fail_next_compile("syntax error");
+
+        let result = Shader::from_source(&gl, &vert_source(), gl::VERTEX_SHADER);
+
+        assert!(result.err().unwrap().starts_with("syntax error"));
+        assert!(mock.live_objects().is_empty());
+    }
+
+    #[test]
+    fn link_error_should_return_log_and_delete_program() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        mock.fail_next_link("missing fragment shader");
+
+        let shader = Shader::from_vert_source(&gl, &vert_source()).unwrap();
+        let result = Program::from_shaders(&gl, &[shader]);
+
+        assert!(result.err().unwrap().starts_with("missing fragment shader"));
+        assert!(mock.live_objects().is_empty());
+    }
+}
@@ -1,8 +1,33 @@
 use gl;
-use resources::{Resources, ResourcePath};
+use image;
+use resources::{Resources, ResourcePath, FromResource};
 use failure;
 use std::os::raw;
 
+/// Image decoded from the resource and converted to 8-bit RGB.
+pub struct RgbImage(image::RgbImage);
+
+impl FromResource for RgbImage {
+    fn from_resource(_resources: &Resources, _path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+        Ok(RgbImage(image::load_from_memory(&data)?.to_rgb()))
+    }
+}
+
+/// Image decoded from the resource, fails if the image has no alpha channel.
+pub struct RgbaImage(image::RgbaImage);
+
+impl FromResource for RgbaImage {
+    fn from_resource(_resources: &Resources, path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+        let img = image::load_from_memory(&data)?;
+
+        if let image::ColorType::RGBA(_) = img.color() {
+            Ok(RgbaImage(img.to_rgba()))
+        } else {
+            Err(format_err!("Image {} is not RGBA", path))
+        }
+    }
+}
+
 pub struct TextureLoadOptions<'a> {
     resource_name: &'a ResourcePath,
     format: gl::types::GLenum,
@@ -93,7 +118,8 @@ impl Texture {
 
         match options.format {
             gl::RGB => {
-                let img = res.load_rgb_image(options.resource_name)?;
+                let img = res.load::<RgbImage, _>(options.resource_name)?.get()?;
+                let img = &img.0;
 
                 if options.gen_mipmaps {
                     unsafe {
@@ -125,7 +151,8 @@ impl Texture {
                 }
             },
             gl::RGBA => {
-                let img = res.load_rgba_image(options.resource_name)?;
+                let img = res.load::<RgbaImage, _>(options.resource_name)?.get()?;
+                let img = &img.0;
 
                 if options.gen_mipmaps {
                     unsafe {
@@ -184,4 +211,38 @@ impl Texture {
         }
         self.bind();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "gl_mock"))]
+mod test {
+    use gl::mock::{Mock, Object};
+    use image;
+    use resources::Resources;
+    use resources::backend::InMemory;
+    use super::Texture;
+
+    fn png_rgb(width: u32, height: u32) -> Vec<u8> {
+        let pixels = vec![255u8; (width * height * 3) as usize];
+        let mut png = Vec::new();
+        image::png::PNGEncoder::new(&mut png)
+            .encode(&pixels, width, height, image::ColorType::RGB(8))
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn texture_should_be_deleted_on_drop() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        let res = Resources::new()
+            .loaded_from("test", 0, InMemory::new().with("white.png", &png_rgb(2, 2)));
+
+        let texture = Texture::from_res_rgb("white.png".into()).load(&gl, &res).unwrap();
+        assert_eq!(vec![(Object::Texture, 1)], mock.live_objects());
+        assert_eq!(1, mock.calls_to("TexImage2D").len());
+
+        drop(texture);
+        assert!(mock.live_objects().is_empty());
+        assert!(mock.invalid_deletes().is_empty());
+    }
+}
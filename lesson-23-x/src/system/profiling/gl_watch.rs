@@ -1,11 +1,8 @@
-#[cfg(feature = "gl_debug")]
-pub fn reset() {
-    use gl;
-    gl::profiler_reset();
-}
-
-#[cfg(not(feature = "gl_debug"))]
 pub fn reset() {
+    #[cfg(feature = "gl_debug")]
+    ::gl::profiler_reset();
+    #[cfg(feature = "gl_state_cache")]
+    ::gl::state_cache_reset_count();
 }
 
 #[cfg(feature = "gl_debug")]
@@ -19,6 +16,18 @@ pub fn calls() -> usize {
     0
 }
 
+/// Returns the number of calls that were skipped because they would not change the GL state.
+#[cfg(feature = "gl_state_cache")]
+pub fn saved_calls() -> usize {
+    use gl;
+    gl::state_cache_saved_count()
+}
+
+#[cfg(not(feature = "gl_state_cache"))]
+pub fn saved_calls() -> usize {
+    0
+}
+
 #[cfg(feature = "gl_debug")]
 pub fn errors() -> usize {
     use gl;
@@ -28,4 +37,20 @@ pub fn errors() -> usize {
 #[cfg(not(feature = "gl_debug"))]
 pub fn errors() -> usize {
     0
-}
\ No newline at end of file
+}
+
+/// Prints the GL functions that were called the most since the last reset.
+#[cfg(feature = "gl_debug")]
+pub fn print_top_calls(count: usize) {
+    use gl;
+    for stats in gl::profiler_fn_stats().into_iter().take(count) {
+        println!(
+            "gl{}: {} calls, {} errors, {:?}",
+            stats.name, stats.calls, stats.errors, stats.time
+        );
+    }
+}
+
+#[cfg(not(feature = "gl_debug"))]
+pub fn print_top_calls(_count: usize) {
+}
@@ -0,0 +1,12 @@
+extern crate resources;
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    resources::embed::embed_dir(manifest_dir.join("core"), out_dir.join("core.rs"))
+        .expect("failed to embed core resources");
+}
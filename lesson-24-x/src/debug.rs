@@ -1,26 +1,28 @@
 use failure;
 
+/// Formats the error starting from the outermost context, for example:
+///
+/// ```text
+/// Failed to load shader shaders/shiny
+///    caused by: Failed reading shaders/shiny.vert from loader core
+///    caused by: Item not found
+/// ```
 pub fn failure_to_string(e: failure::Error) -> String {
     use std::fmt::Write;
 
     let mut result = String::new();
 
-    for (i, cause) in e.iter_chain().collect::<Vec<_>>().into_iter().rev().enumerate() {
+    for (i, cause) in e.iter_chain().enumerate() {
         if i > 0 {
-            let _ = writeln!(&mut result, "   Which caused the following issue:");
-        }
-        let _ = write!(&mut result, "{}", cause);
-        if let Some(backtrace) = cause.backtrace() {
-            let backtrace_str = format!("{}", backtrace);
-            if backtrace_str.len() > 0 {
-                let _ = writeln!(&mut result, " This happened at {}", backtrace);
-            } else {
-                let _ = writeln!(&mut result);
-            }
-        } else {
-            let _ = writeln!(&mut result);
+            let _ = write!(&mut result, "   caused by: ");
         }
+        let _ = writeln!(&mut result, "{}", cause);
+    }
+
+    let backtrace_str = format!("{}", e.backtrace());
+    if backtrace_str.len() > 0 {
+        let _ = writeln!(&mut result, "This happened at {}", backtrace_str);
     }
 
     result
-}
\ No newline at end of file
+}
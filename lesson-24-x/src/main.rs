@@ -1,5 +1,6 @@
 extern crate gl;
 extern crate failure;
+#[macro_use]
 extern crate resources;
 extern crate lesson_24_x_render_gl;
 #[macro_use]
@@ -15,18 +16,25 @@ fn main() {
 }
 
 fn run() -> Result<(), failure::Error> {
-//    let resources = resources::Resources::new()
-//        .loaded_from(
-//            "core", 0,
-//            resources::backend::FileSystem::from_rel_path(env!("CARGO_MANIFEST_DIR"), "core")
-//                .with_write()
-//                .with_watch(),
-//        );
-//
-//    let config_resource = resources.resource("Config.toml");
-//    let config = config_resource.any_reader().and_then(|r| r.read_vec().ok());
-
-//    println!("Config: {:#?}", config);
+    // core files are embedded into the executable, and files on disk override them during development
+    let resources = profiling::resources::enable(resources::Resources::new())
+        .loaded_from(
+            "embedded", 0,
+            resources::backend::InMemory::from_embedded(embedded_resources!("core.rs")),
+        )
+        .loaded_from(
+            "core", 1,
+            resources::backend::FileSystem::from_rel_path(env!("CARGO_MANIFEST_DIR"), "core")
+                .with_write()
+                .with_watch(),
+        );
+
+    let config_resource = resources.resource("Config.toml");
+    let config = config_resource.get().ok();
+
+    println!("Config: {:#?}", config.map(|c| String::from_utf8_lossy(&c).into_owned()));
+
+    profiling::resources::print(&resources);
 
     Ok(())
 }
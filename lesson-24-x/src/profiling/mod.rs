@@ -1,2 +1,3 @@
 pub mod alloc;
 pub mod gl_calls;
+pub mod resources;
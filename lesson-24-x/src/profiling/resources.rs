@@ -0,0 +1,18 @@
+use resources::Resources;
+
+/// Enables access statistics of the resources if the `resource_stats` feature is on.
+#[cfg(feature = "resource_stats")]
+pub fn enable(resources: Resources) -> Resources {
+    resources.with_access_stats()
+}
+
+#[cfg(not(feature = "resource_stats"))]
+pub fn enable(resources: Resources) -> Resources {
+    resources
+}
+
+/// Prints the statistics, prints nothing if they are disabled.
+pub fn print(resources: &Resources) {
+    use std::io;
+    let _ = resources.dump_access_stats(&mut io::stdout());
+}
@@ -1,22 +1,84 @@
 extern crate gl_generator;
-extern crate gl_generator_profiling_struct;
+
+mod generator;
 
 use gl_generator::{Registry, Fallbacks, Api, Profile};
-use gl_generator_profiling_struct::ProfilingStructGenerator;
+use generator::ProfilingGenerator;
 use std::env;
 use std::fs::File;
 use std::path::Path;
 
+/// API and version features, at most one of them besides the default target can be enabled.
+const TARGETS: &[(&str, Api, (u8, u8))] = &[
+    ("gl_3_3", Api::Gl, (3, 3)),
+    ("gl_4_5", Api::Gl, (4, 5)),
+    ("gles_3_0", Api::Gles2, (3, 0)),
+];
+
+/// Target in default features, used only when no other target is enabled.
+const DEFAULT_TARGET: &str = "gl_4_5";
+
+/// Extension features, the API that has them and the extensions they enable.
+///
+/// Extensions of other APIs are skipped, so that default extensions do not break other targets.
+const EXTENSIONS: &[(&str, Api, &str)] = &[
+    ("ext_nv_command_list", Api::Gl, "GL_NV_command_list"),
+];
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let mut file_gl = File::create(&Path::new(&out_dir).join("bindings.rs")).unwrap();
 
-    let registry = Registry::new(Api::Gl, (4, 5), Profile::Core, Fallbacks::All, [
-        "GL_NV_command_list",
-    ]);
+    // the default target is only a fallback, so a dependent that picks another target without
+    // disabling default features, or shares the crate with members that keep them, still builds
+    let mut enabled_targets: Vec<_> = TARGETS.iter()
+        .filter(|&&(feature, _, _)| feature != DEFAULT_TARGET && has_feature(feature))
+        .collect();
+    if enabled_targets.is_empty() {
+        enabled_targets.extend(TARGETS.iter().filter(|&&(feature, _, _)| feature == DEFAULT_TARGET && has_feature(feature)));
+    }
+    let (api, version) = match enabled_targets.as_slice() {
+        &[&(_, api, version)] => (api, version),
+        &[] => panic!(
+            "enable one of the gl features {:?}",
+            TARGETS.iter().map(|&(feature, _, _)| feature).collect::<Vec<_>>()
+        ),
+        _ => panic!(
+            "gl features {:?} conflict, enable only one of them",
+            enabled_targets.iter().map(|&&(feature, _, _)| feature).collect::<Vec<_>>()
+        ),
+    };
+
+    let profile = if has_feature("profile_compatibility") {
+        Profile::Compatibility
+    } else {
+        Profile::Core
+    };
+
+    // extensions that have no feature can be listed in GL_EXTENSIONS, separated by commas
+    println!("cargo:rerun-if-env-changed=GL_EXTENSIONS");
+    let extra_extensions = env::var("GL_EXTENSIONS").unwrap_or_default();
+    let extensions: Vec<&str> = EXTENSIONS.iter()
+        .filter(|&&(feature, extension_api, _)| extension_api == api && has_feature(feature))
+        .map(|&(_, _, extension)| extension)
+        .chain(extra_extensions.split(',').map(str::trim).filter(|e| !e.is_empty()))
+        .collect();
+
+    let registry = Registry::new(api, version, profile, Fallbacks::All, extensions);
+
+    let generator = ProfilingGenerator {
+        profile: has_feature("debug"),
+        timing: has_feature("debug_timing"),
+        trace: has_feature("trace"),
+        mock: has_feature("mock"),
+    };
 
     registry.write_bindings(
-        ProfilingStructGenerator,
+        generator,
         &mut file_gl
     ).unwrap();
-}
\ No newline at end of file
+}
+
+fn has_feature(feature: &str) -> bool {
+    env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some()
+}
@@ -0,0 +1,519 @@
+use gl_generator::{Cmd, Generator, Registry};
+use gl_generator::generators::{gen_enum_item, gen_parameters, gen_symbol_name, gen_types};
+use std::io;
+
+/// Generates the `Gl` struct, with per-function call statistics if `profile` is enabled,
+/// the recording and replay of calls for `::trace` if `trace` is enabled, and the functions
+/// of `::mock` if `mock` is enabled.
+///
+/// Output is the same as of `gl_generator::StructGenerator`, except that the struct is named
+/// `Gl` for all APIs, so that the `gl::Gl` wrapper works with GLES bindings too.
+pub struct ProfilingGenerator {
+    /// Count calls of each function, and the errors returned by `glGetError` after them.
+    pub profile: bool,
+    /// Also measure the time spent in each function.
+    pub timing: bool,
+    /// Record calls when `::trace` is recording, and generate `trace_replay_call`.
+    pub trace: bool,
+    /// Generate functions that forward calls to `::mock`, and `mock_fn_ptr`. Requires `trace`.
+    pub mock: bool,
+}
+
+/// Pointer parameters whose contents are recorded in traces, with the size in bytes.
+const TRACE_PAYLOADS: &[(&str, &str, &str)] = &[
+    ("BufferData", "data", "size"),
+    ("BufferSubData", "data", "size"),
+    ("BufferStorage", "data", "size"),
+    ("NamedBufferData", "data", "size"),
+    ("NamedBufferSubData", "data", "size"),
+    ("NamedBufferStorage", "data", "size"),
+    ("CompressedTexImage1D", "data", "imageSize"),
+    ("CompressedTexImage2D", "data", "imageSize"),
+    ("CompressedTexImage3D", "data", "imageSize"),
+    ("CompressedTexSubImage1D", "data", "imageSize"),
+    ("CompressedTexSubImage2D", "data", "imageSize"),
+    ("CompressedTexSubImage3D", "data", "imageSize"),
+    ("CompressedTextureSubImage1D", "data", "imageSize"),
+    ("CompressedTextureSubImage2D", "data", "imageSize"),
+    ("CompressedTextureSubImage3D", "data", "imageSize"),
+];
+
+/// Sizes in bytes of one value of `Uniform*v` and `ProgramUniform*v` functions, by the name
+/// after `Uniform`.
+const UNIFORM_VALUE_SIZES: &[(&str, usize)] = &[
+    ("1fv", 4), ("2fv", 8), ("3fv", 12), ("4fv", 16),
+    ("1iv", 4), ("2iv", 8), ("3iv", 12), ("4iv", 16),
+    ("1uiv", 4), ("2uiv", 8), ("3uiv", 12), ("4uiv", 16),
+    ("1dv", 8), ("2dv", 16), ("3dv", 24), ("4dv", 32),
+    ("Matrix2fv", 16), ("Matrix3fv", 36), ("Matrix4fv", 64),
+    ("Matrix2x3fv", 24), ("Matrix3x2fv", 24), ("Matrix2x4fv", 32),
+    ("Matrix4x2fv", 32), ("Matrix3x4fv", 48), ("Matrix4x3fv", 48),
+    ("Matrix2dv", 32), ("Matrix3dv", 72), ("Matrix4dv", 128),
+    ("Matrix2x3dv", 48), ("Matrix3x2dv", 48), ("Matrix2x4dv", 64),
+    ("Matrix4x2dv", 64), ("Matrix3x4dv", 96), ("Matrix4x3dv", 96),
+];
+
+/// Returns the pointer parameter whose contents are recorded in traces, and the expression
+/// of their size in bytes.
+fn trace_payload(cmd: &Cmd) -> Option<(String, String)> {
+    let name = &cmd.proto.ident[..];
+    if let Some(&(_, data, size)) = TRACE_PAYLOADS.iter().find(|&&(payload_name, _, _)| payload_name == name) {
+        return Some((data.into(), size.into()));
+    }
+
+    let uniform = name.trim_start_matches("Program");
+    if uniform.starts_with("Uniform") {
+        return UNIFORM_VALUE_SIZES.iter()
+            .find(|&&(suffix, _)| &uniform["Uniform".len()..] == suffix)
+            .map(|&(_, size)| ("value".into(), format!("count * {}", size)));
+    }
+
+    let is_image = ["TexImage", "TexSubImage", "TextureSubImage"].iter().any(|prefix| name.starts_with(prefix));
+    if is_image && param(cmd, "pixels").is_some() {
+        let dimension = |ident| param(cmd, ident).unwrap_or("1");
+        return Some((
+            "pixels".into(),
+            format!(
+                "::trace::pixels_len({}, {}, {}, {}, {})",
+                param(cmd, "format")?, param(cmd, "type_").or_else(|| param(cmd, "type"))?,
+                dimension("width"), dimension("height"), dimension("depth")
+            ),
+        ));
+    }
+
+    None
+}
+
+/// Returns true for `TexImage*`, `TexSubImage*` and their compressed and DSA variants.
+fn reads_pixels(name: &str) -> bool {
+    ["TexImage", "TexSubImage", "TextureSubImage"].iter().any(|image| name.contains(image))
+}
+
+fn param<'a>(cmd: &'a Cmd, ident: &str) -> Option<&'a str> {
+    cmd.params.iter()
+        .find(|param| param.ident == ident)
+        .map(|param| &param.ident[..])
+}
+
+/// The way a parameter is recorded in traces.
+#[derive(Copy, Clone)]
+enum ArgKind {
+    Signed,
+    Unsigned,
+    F32,
+    F64,
+    Pointer,
+    /// Handle that is a pointer on some platforms.
+    Handle,
+    Callback,
+}
+
+fn arg_kind(ty: &str) -> ArgKind {
+    if ty.starts_with('*') {
+        return ArgKind::Pointer;
+    }
+    match ty.trim_start_matches("types::") {
+        "GLfloat" | "GLclampf" => ArgKind::F32,
+        "GLdouble" | "GLclampd" => ArgKind::F64,
+        "GLbyte" | "GLshort" | "GLint" | "GLsizei" | "GLintptr" | "GLsizeiptr" | "GLint64"
+        | "GLintptrARB" | "GLsizeiptrARB" | "GLint64EXT" | "GLfixed" | "GLclampx" | "GLvdpauSurfaceNV" => ArgKind::Signed,
+        "GLsync" | "GLhandleARB" | "GLeglImageOES" | "GLeglClientBufferEXT" => ArgKind::Handle,
+        ty if ty.contains("PROC") => ArgKind::Callback,
+        _ => ArgKind::Unsigned,
+    }
+}
+
+impl Generator for ProfilingGenerator {
+    fn write<W>(&self, registry: &Registry, dest: &mut W) -> io::Result<()>
+        where W: io::Write
+    {
+        write_header(self, dest)?;
+        write_type_aliases(registry, dest)?;
+        write_enums(registry, dest)?;
+        write_fnptr_struct_def(dest)?;
+        write_panicking_fns(registry, dest)?;
+        if self.profile || self.trace {
+            write_fn_names(registry, dest)?;
+        }
+        if self.profile {
+            write_profiler_statics(registry, dest)?;
+        }
+        write_struct(registry, dest)?;
+        write_impl(self, registry, dest)?;
+        if self.trace {
+            write_trace_replay(registry, dest)?;
+        }
+        if self.mock {
+            write_mock_fns(registry, dest)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_header<W>(generator: &ProfilingGenerator, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, r#"
+        mod __gl_imports {{
+            pub use std::mem;
+            pub use std::marker::Send;
+            pub use std::os::raw;"#)?;
+    if generator.profile {
+        writeln!(dest, "pub use std::sync::atomic::{{AtomicUsize, AtomicU64, Ordering}};")?;
+    }
+    if generator.timing {
+        writeln!(dest, "pub use std::time::Instant;")?;
+    }
+    writeln!(dest, "}}")
+}
+
+fn write_type_aliases<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, r#"
+        pub mod types {{
+            #![allow(non_camel_case_types, non_snake_case, dead_code, missing_copy_implementations)]
+    "#)?;
+    gen_types(registry.api, dest)?;
+    writeln!(dest, "}}")
+}
+
+fn write_enums<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    for enm in &registry.enums {
+        gen_enum_item(enm, "types::", dest)?;
+    }
+    Ok(())
+}
+
+fn write_fnptr_struct_def<W>(dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "
+        #[allow(dead_code, missing_copy_implementations)]
+        #[derive(Clone)]
+        pub struct FnPtr {{
+            /// The function pointer that will be used when calling the function.
+            f: *const __gl_imports::raw::c_void,
+            /// True if the pointer points to a real function, false if points to a `panic!` fn.
+            is_loaded: bool,
+        }}
+
+        impl FnPtr {{
+            /// Creates a `FnPtr` from a load attempt.
+            fn new(ptr: *const __gl_imports::raw::c_void) -> FnPtr {{
+                if ptr.is_null() {{
+                    FnPtr {{
+                        f: missing_fn_panic as *const __gl_imports::raw::c_void,
+                        is_loaded: false
+                    }}
+                }} else {{
+                    FnPtr {{ f: ptr, is_loaded: true }}
+                }}
+            }}
+
+            /// Returns `true` if the function has been successfully loaded.
+            ///
+            /// If it returns `false`, calling the corresponding function will fail.
+            #[inline]
+            #[allow(dead_code)]
+            pub fn is_loaded(&self) -> bool {{
+                self.is_loaded
+            }}
+        }}
+    ")
+}
+
+fn write_panicking_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "
+        #[inline(never)]
+        fn missing_fn_panic() -> ! {{
+            panic!(\"{api} function was not loaded\")
+        }}",
+        api = registry.api)
+}
+
+/// Creates the names of all functions, indices of other statics are in the same order.
+fn write_fn_names<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "pub(crate) static FN_NAMES: [&str; {}] = [", registry.cmds.len())?;
+    for cmd in &registry.cmds {
+        writeln!(dest, "\"{}\",", cmd.proto.ident)?;
+    }
+    writeln!(dest, "];")
+}
+
+/// Creates the counters of all functions.
+fn write_profiler_statics<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    let len = registry.cmds.len();
+
+    for &(name, ty) in &[
+        ("PROFILER_FN_CALLS", "AtomicUsize"),
+        ("PROFILER_FN_ERRORS", "AtomicUsize"),
+        ("PROFILER_FN_NANOS", "AtomicU64"),
+    ] {
+        writeln!(dest, "pub(crate) static {}: [__gl_imports::{}; {}] = [", name, ty, len)?;
+        for _ in 0..len {
+            writeln!(dest, "__gl_imports::{}::new(0),", ty)?;
+        }
+        writeln!(dest, "];")?;
+    }
+    Ok(())
+}
+
+fn write_struct<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "
+        #[allow(non_camel_case_types, non_snake_case, dead_code)]
+        #[derive(Clone)]
+        pub struct Gl {{")?;
+
+    for cmd in &registry.cmds {
+        if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
+            writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
+        }
+        writeln!(dest, "pub {name}: FnPtr,", name = cmd.proto.ident)?;
+    }
+    writeln!(dest, "_priv: ()")?;
+
+    writeln!(dest, "}}")
+}
+
+fn write_impl<W>(generator: &ProfilingGenerator, registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "impl Gl {{
+            /// Load each OpenGL symbol using a custom load function. This allows for the
+            /// use of functions like `glfwGetProcAddress` or `SDL_GL_GetProcAddress`.
+            ///
+            /// ~~~ignore
+            /// let gl = Gl::load_with(|s| glfw.get_proc_address(s));
+            /// ~~~
+            #[allow(dead_code, unused_variables)]
+            pub fn load_with<F>(mut loadfn: F) -> Gl where F: FnMut(&'static str) -> *const __gl_imports::raw::c_void {{
+                #[inline(never)]
+                fn do_metaloadfn(loadfn: &mut FnMut(&'static str) -> *const __gl_imports::raw::c_void,
+                                 symbol: &'static str,
+                                 symbols: &[&'static str])
+                                 -> *const __gl_imports::raw::c_void {{
+                    let mut ptr = loadfn(symbol);
+                    if ptr.is_null() {{
+                        for &sym in symbols {{
+                            ptr = loadfn(sym);
+                            if !ptr.is_null() {{ break; }}
+                        }}
+                    }}
+                    ptr
+                }}
+                let mut metaloadfn = |symbol: &'static str, symbols: &[&'static str]| {{
+                    do_metaloadfn(&mut loadfn, symbol, symbols)
+                }};
+                Gl {{")?;
+
+    for cmd in &registry.cmds {
+        writeln!(dest,
+            "{name}: FnPtr::new(metaloadfn(\"{symbol}\", &[{fallbacks}])),",
+            name = cmd.proto.ident,
+            symbol = gen_symbol_name(registry.api, &cmd.proto.ident),
+            fallbacks = match registry.aliases.get(&cmd.proto.ident) {
+                Some(fbs) => fbs.iter()
+                    .map(|name| format!("\"{}\"", gen_symbol_name(registry.api, &name)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => String::new(),
+            },
+        )?;
+    }
+    writeln!(dest, "_priv: ()")?;
+
+    writeln!(dest, "}}
+        }}")?;
+
+    let has_get_error = registry.cmds.iter().any(|cmd| cmd.proto.ident == "GetError");
+
+    for (index, cmd) in registry.cmds.iter().enumerate() {
+        let call = format!(
+            "__gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
+                (self.{name}.f)({idents})",
+            name = cmd.proto.ident,
+            typed_params = gen_parameters(cmd, false, true).join(", "),
+            return_suffix = cmd.proto.ty,
+            idents = gen_parameters(cmd, true, false).join(", "),
+        );
+
+        let mut body = if generator.profile {
+            profiled_call(generator, index, &call, has_get_error && cmd.proto.ident != "GetError")
+        } else {
+            call
+        };
+        if generator.trace {
+            let mut record = format!("::trace::record({}, &[{}]);", index, trace_args(cmd));
+            match &cmd.proto.ident[..] {
+                "PixelStorei" => record.push_str(" ::trace::pixel_store(pname, param);"),
+                "BindBuffer" => record.push_str(" ::trace::bind_buffer(target, buffer);"),
+                "DeleteBuffers" => record.push_str(" ::trace::delete_buffers(n, buffers);"),
+                _ => (),
+            }
+            body = format!("if ::trace::is_recording() {{ {} }}\n{}", record, body);
+        }
+
+        writeln!(dest,
+            "#[allow(non_snake_case, unused_variables, dead_code)]
+            #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{ {body} }}",
+            name = cmd.proto.ident,
+            params = gen_parameters(cmd, true, true).join(", "),
+            return_suffix = cmd.proto.ty,
+            body = body,
+        )?;
+    }
+
+    writeln!(dest, "}}
+
+        unsafe impl __gl_imports::Send for Gl {{}}")
+}
+
+/// Returns the `::trace::Arg` expressions of the call parameters.
+///
+/// Besides `trace_payload`, the source strings of `ShaderSource` and parameters named `name` that
+/// are C strings are recorded as payloads.
+fn trace_args(cmd: &Cmd) -> String {
+    let payload = trace_payload(cmd);
+    // texture uploads read the pixels from the buffer bound to `PIXEL_UNPACK_BUFFER` if there is one
+    let payload_fn = if reads_pixels(&cmd.proto.ident) { "pixels" } else { "payload" };
+
+    cmd.params.iter()
+        .map(|param| match (arg_kind(&param.ty), &payload) {
+            (ArgKind::Pointer, &Some((ref data, ref size))) if param.ident == *data =>
+                format!("::trace::Arg::{}({} as *const u8, ({}) as isize)", payload_fn, param.ident, size),
+            (ArgKind::Pointer, _) if cmd.proto.ident == "ShaderSource" && param.ident == "string" =>
+                String::from("::trace::Arg::strings(string as *const *const u8, count as isize, length)"),
+            (ArgKind::Pointer, _) if param.ident == "name" && param.ty.ends_with("const types::GLchar") =>
+                String::from("::trace::Arg::c_string(name as *const u8)"),
+            (ArgKind::Signed, _) => format!("::trace::Arg::Int({} as i64)", param.ident),
+            (ArgKind::Unsigned, _) => format!("::trace::Arg::Uint({} as u64)", param.ident),
+            (ArgKind::F32, _) => format!("::trace::Arg::F32({} as f32)", param.ident),
+            (ArgKind::F64, _) => format!("::trace::Arg::F64({} as f64)", param.ident),
+            (ArgKind::Pointer, _) => format!("::trace::Arg::Pointer({} as usize as u64)", param.ident),
+            (ArgKind::Handle, _) => format!("::trace::Arg::Uint({} as usize as u64)", param.ident),
+            (ArgKind::Callback, _) => String::from("::trace::Arg::Callback(true)"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Creates the function that calls the function at the index with arguments read from a trace.
+///
+/// Functions with callback parameters are not replayed, as the callbacks are not recorded.
+/// `ShaderSource` is replayed with the recorded source as one string.
+fn write_trace_replay<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    writeln!(dest, "
+        #[allow(unused_variables)]
+        pub(crate) unsafe fn trace_replay_call(gl: &Gl, index: usize, args: &[::trace::Arg], scratch: &mut Vec<u8>) -> bool {{
+            match index {{")?;
+
+    for (index, cmd) in registry.cmds.iter().enumerate() {
+        if cmd.params.iter().any(|param| match arg_kind(&param.ty) { ArgKind::Callback => true, _ => false }) {
+            continue;
+        }
+        if cmd.proto.ident == "ShaderSource" {
+            writeln!(dest,
+                "{} if args.len() == 4 => {{
+                    let source = args[2].to_ptr(scratch) as *const types::GLchar;
+                    let length = args[2].payload_len() as types::GLint;
+                    gl.ShaderSource(args[0].to_u64() as types::GLuint, 1, &source, &length);
+                    true
+                }},",
+                index)?;
+            continue;
+        }
+        let args = cmd.params.iter().enumerate()
+            .map(|(i, param)| match arg_kind(&param.ty) {
+                ArgKind::Signed | ArgKind::Unsigned => format!("args[{}].to_u64() as {}", i, param.ty),
+                ArgKind::F32 | ArgKind::F64 => format!("args[{}].to_f64() as {}", i, param.ty),
+                ArgKind::Pointer => format!("args[{}].to_ptr(scratch) as {}", i, param.ty),
+                ArgKind::Handle => format!("args[{}].to_u64() as usize as {}", i, param.ty),
+                ArgKind::Callback => unreachable!("functions with callbacks are not replayed"),
+            })
+            .collect::<Vec<_>>();
+        writeln!(dest, "{} if args.len() == {} => {{ gl.{}({}); true }},", index, args.len(), cmd.proto.ident, args.join(", "))?;
+    }
+
+    writeln!(dest, "
+                _ => false,
+            }}
+        }}")
+}
+
+/// Creates a function for each GL function that passes the call to `::mock::call`, and
+/// `mock_fn_ptr` that returns the function at the index.
+fn write_mock_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+    where W: io::Write
+{
+    for (index, cmd) in registry.cmds.iter().enumerate() {
+        let call = format!("::mock::call({}, vec![{}])", index, trace_args(cmd));
+        let body = match cmd.proto.ty.as_ref() {
+            "()" => format!("{};", call),
+            ty => match arg_kind(ty) {
+                ArgKind::Pointer | ArgKind::Handle => format!("{} as usize as {}", call, ty),
+                _ => format!("{} as {}", call, ty),
+            },
+        };
+        writeln!(dest,
+            "#[allow(non_snake_case, unused_variables, unused_unsafe)]
+            extern \"system\" fn mock_{name}({params}) -> {return_suffix} {{ unsafe {{ {body} }} }}",
+            name = cmd.proto.ident,
+            params = gen_parameters(cmd, true, true).join(", "),
+            return_suffix = cmd.proto.ty,
+            body = body,
+        )?;
+    }
+
+    writeln!(dest, "
+        pub(crate) fn mock_fn_ptr(index: usize) -> *const __gl_imports::raw::c_void {{
+            match index {{")?;
+    for (index, cmd) in registry.cmds.iter().enumerate() {
+        writeln!(dest, "{} => mock_{} as *const __gl_imports::raw::c_void,", index, cmd.proto.ident)?;
+    }
+    writeln!(dest, "
+                _ => 0 as *const __gl_imports::raw::c_void,
+            }}
+        }}")
+}
+
+/// Wraps the call with the counters of the function at `index`.
+fn profiled_call(generator: &ProfilingGenerator, index: usize, call: &str, check_error: bool) -> String {
+    let mut body = String::new();
+
+    if generator.timing {
+        body.push_str("let started = __gl_imports::Instant::now();\n");
+    }
+    body.push_str(&format!("let r = {};\n", call));
+    if generator.timing {
+        body.push_str(&format!(
+            "let elapsed = started.elapsed();\n\
+             PROFILER_FN_NANOS[{}].fetch_add(elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64, __gl_imports::Ordering::Relaxed);\n",
+            index
+        ));
+    }
+    body.push_str(&format!("PROFILER_FN_CALLS[{}].fetch_add(1, __gl_imports::Ordering::Relaxed);\n", index));
+    if check_error {
+        body.push_str(&format!(
+            "if __gl_imports::mem::transmute::<_, extern \"system\" fn() -> u32>(self.GetError.f)() != 0 {{\n\
+                 PROFILER_FN_ERRORS[{}].fetch_add(1, __gl_imports::Ordering::Relaxed);\n\
+             }}\n",
+            index
+        ));
+    }
+    body.push_str("r");
+
+    body
+}
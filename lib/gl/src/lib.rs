@@ -5,12 +5,27 @@ mod bindings {
 use std::rc::Rc;
 use std::ops::Deref;
 
+#[cfg(feature = "debug")]
+mod profiler;
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "state_cache")]
+mod state_cache;
+
 pub use bindings::*;
 pub use bindings::Gl as InnerGl;
+#[cfg(feature = "debug")]
+pub use profiler::{FnStats, profiler_reset, profiler_call_count, profiler_err_count, profiler_fn_stats};
+#[cfg(feature = "state_cache")]
+pub use state_cache::{state_cache_saved_count, state_cache_reset_count};
 
 #[derive(Clone)]
 pub struct Gl {
     inner: Rc<bindings::Gl>,
+    #[cfg(feature = "state_cache")]
+    state: Rc<state_cache::StateCache>,
 }
 
 impl Gl {
@@ -18,7 +33,9 @@ impl Gl {
         where F: FnMut(&'static str) -> *const types::GLvoid
     {
         Gl {
-            inner: Rc::new(bindings::Gl::load_with(loadfn))
+            inner: Rc::new(bindings::Gl::load_with(loadfn)),
+            #[cfg(feature = "state_cache")]
+            state: Rc::new(state_cache::StateCache::default()),
         }
     }
 }
@@ -0,0 +1,353 @@
+//! GL functions implemented in Rust, for tests that run without a GL context.
+//!
+//! The mock hands out object names, records calls, and tracks which objects are alive, so
+//! tests can check that everything that was created is deleted:
+//!
+//! ```ignore
+//! let mock = gl::mock::Mock::new();
+//! let gl = mock.gl();
+//!
+//! drop(Buffer::new_array(&gl));
+//!
+//! assert_eq!(1, mock.calls_to("DeleteBuffers").len());
+//! assert!(mock.live_objects().is_empty());
+//! ```
+//!
+//! Compile and link failures and `glGetError` results can be injected. Functions that the mock
+//! does not implement only record the call, and do not write to output parameters.
+//!
+//! Calls go to the mock that created the last `Gl` on the calling thread. With the `debug`
+//! feature, the `glGetError` calls made by the profiler after each call are recorded too.
+
+use bindings::{self, types, FN_NAMES};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::ptr;
+use std::rc::Rc;
+use trace::{Arg, Call};
+use Gl;
+
+/// Kind of GL object tracked by the mock.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Object {
+    Buffer,
+    VertexArray,
+    Texture,
+    Framebuffer,
+    Renderbuffer,
+    Query,
+    Sampler,
+    TransformFeedback,
+    ProgramPipeline,
+    Shader,
+    Program,
+}
+
+/// Objects created and deleted by `Gen*`, `Create*` and `Delete*` functions with these suffixes.
+const OBJECT_FUNCTIONS: &[(&str, Object)] = &[
+    ("Buffers", Object::Buffer),
+    ("VertexArrays", Object::VertexArray),
+    ("Textures", Object::Texture),
+    ("Framebuffers", Object::Framebuffer),
+    ("Renderbuffers", Object::Renderbuffer),
+    ("Queries", Object::Query),
+    ("Samplers", Object::Sampler),
+    ("TransformFeedbacks", Object::TransformFeedback),
+    ("ProgramPipelines", Object::ProgramPipeline),
+];
+
+const VERSION_STRING: &[u8] = b"mock\0";
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<RefCell<State>>>> = RefCell::new(None);
+}
+
+/// Mock GL implementation.
+#[derive(Default)]
+pub struct Mock {
+    state: Rc<RefCell<State>>,
+}
+
+impl Mock {
+    pub fn new() -> Mock {
+        Mock::default()
+    }
+
+    /// Returns `Gl` whose functions call this mock, and makes it the mock of the current thread.
+    pub fn gl(&self) -> Gl {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.state.clone()));
+
+        let indices: HashMap<String, usize> = FN_NAMES.iter()
+            .enumerate()
+            .map(|(index, name)| (format!("gl{}", name), index))
+            .collect();
+
+        Gl::load_with(|symbol| match indices.get(symbol) {
+            Some(&index) => bindings::mock_fn_ptr(index),
+            None => ptr::null(),
+        })
+    }
+
+    /// Returns all recorded calls, oldest first.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state.borrow().calls.clone()
+    }
+
+    /// Returns recorded calls of the function, the name is without the `gl` prefix.
+    pub fn calls_to(&self, name: &str) -> Vec<Call> {
+        self.state.borrow().calls.iter()
+            .filter(|call| call.name == name)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_calls(&self) {
+        self.state.borrow_mut().calls.clear();
+    }
+
+    /// Returns objects that were created and not deleted.
+    pub fn live_objects(&self) -> Vec<(Object, types::GLuint)> {
+        self.state.borrow().live.iter().cloned().collect()
+    }
+
+    /// Returns names that were deleted while not alive, for example deleted twice.
+    pub fn invalid_deletes(&self) -> Vec<(Object, types::GLuint)> {
+        self.state.borrow().invalid_deletes.clone()
+    }
+
+    /// Makes the next `glCompileShader` fail with the log.
+    pub fn fail_next_compile(&self, log: &str) {
+        self.state.borrow_mut().next_compile_error = Some(log.into());
+    }
+
+    /// Makes the next `glLinkProgram` fail with the log.
+    pub fn fail_next_link(&self, log: &str) {
+        self.state.borrow_mut().next_link_error = Some(log.into());
+    }
+
+    /// Adds the error to the ones returned by `glGetError`, oldest first.
+    pub fn push_error(&self, error: types::GLenum) {
+        self.state.borrow_mut().errors.push_back(error);
+    }
+}
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+    last_name: types::GLuint,
+    live: BTreeSet<(Object, types::GLuint)>,
+    invalid_deletes: Vec<(Object, types::GLuint)>,
+    next_compile_error: Option<String>,
+    next_link_error: Option<String>,
+    /// Logs of shaders and programs that failed to compile or link.
+    failed: HashMap<(Object, types::GLuint), String>,
+    errors: VecDeque<types::GLenum>,
+}
+
+/// Handles the call of the function at the index, used by the generated bindings.
+///
+/// Returns the result converted to `u64`.
+pub(crate) unsafe fn call(index: usize, args: Vec<Arg>) -> u64 {
+    let state = CURRENT.with(|current| current.borrow().clone())
+        .expect("GL function called on a thread without gl::mock::Mock");
+    let mut state = state.borrow_mut();
+
+    let name = FN_NAMES[index];
+    let result = state.respond(name, &args);
+    state.calls.push(Call { name: name.into(), args });
+    result
+}
+
+impl State {
+    unsafe fn respond(&mut self, name: &str, args: &[Arg]) -> u64 {
+        if let Some(object) = object_of(name, "Gen").or_else(|| object_of(name, "Create")) {
+            let names = args[args.len() - 1].to_u64() as usize as *mut types::GLuint;
+            for i in 0..args[args.len() - 2].to_u64() as usize {
+                let created = self.create(object);
+                if !names.is_null() {
+                    *names.add(i) = created;
+                }
+            }
+            return 0;
+        }
+        if let Some(object) = object_of(name, "Delete") {
+            let names = args[1].to_u64() as usize as *const types::GLuint;
+            if !names.is_null() {
+                for i in 0..args[0].to_u64() as usize {
+                    self.delete(object, *names.add(i));
+                }
+            }
+            return 0;
+        }
+
+        match name {
+            "CreateShader" => self.create(Object::Shader) as u64,
+            "CreateProgram" => self.create(Object::Program) as u64,
+            "DeleteShader" => { self.delete(Object::Shader, args[0].to_u64() as types::GLuint); 0 },
+            "DeleteProgram" => { self.delete(Object::Program, args[0].to_u64() as types::GLuint); 0 },
+            "CompileShader" => {
+                if let Some(log) = self.next_compile_error.take() {
+                    self.failed.insert((Object::Shader, args[0].to_u64() as types::GLuint), log);
+                }
+                0
+            },
+            "LinkProgram" => {
+                if let Some(log) = self.next_link_error.take() {
+                    self.failed.insert((Object::Program, args[0].to_u64() as types::GLuint), log);
+                }
+                0
+            },
+            "GetShaderiv" => { self.write_status(Object::Shader, bindings::COMPILE_STATUS, args); 0 },
+            "GetProgramiv" => { self.write_status(Object::Program, bindings::LINK_STATUS, args); 0 },
+            "GetShaderInfoLog" => { self.write_log(Object::Shader, args); 0 },
+            "GetProgramInfoLog" => { self.write_log(Object::Program, args); 0 },
+            "GetError" => self.errors.pop_front().unwrap_or(bindings::NO_ERROR) as u64,
+            "GetString" | "GetStringi" => VERSION_STRING.as_ptr() as usize as u64,
+            "CheckFramebufferStatus" | "CheckNamedFramebufferStatus" => bindings::FRAMEBUFFER_COMPLETE as u64,
+            _ => 0,
+        }
+    }
+
+    fn create(&mut self, object: Object) -> types::GLuint {
+        self.last_name += 1;
+        self.live.insert((object, self.last_name));
+        self.last_name
+    }
+
+    fn delete(&mut self, object: Object, name: types::GLuint) {
+        // deleting 0 is silently ignored by GL
+        if name != 0 && !self.live.remove(&(object, name)) {
+            self.invalid_deletes.push((object, name));
+        }
+    }
+
+    /// Writes the status or info log length of the object to the parameter of `glGet*iv`.
+    unsafe fn write_status(&self, object: Object, status: types::GLenum, args: &[Arg]) {
+        let log = self.failed.get(&(object, args[0].to_u64() as types::GLuint));
+        let pname = args[1].to_u64() as types::GLenum;
+        let params = args[2].to_u64() as usize as *mut types::GLint;
+
+        let value = if pname == status {
+            (if log.is_some() { bindings::FALSE } else { bindings::TRUE }) as types::GLint
+        } else if pname == bindings::INFO_LOG_LENGTH {
+            log.map(|log| log.len() as types::GLint + 1).unwrap_or(0)
+        } else {
+            return;
+        };
+
+        if !params.is_null() {
+            *params = value;
+        }
+    }
+
+    /// Writes the info log of the object to the parameters of `glGet*InfoLog`.
+    unsafe fn write_log(&self, object: Object, args: &[Arg]) {
+        let log = self.failed.get(&(object, args[0].to_u64() as types::GLuint))
+            .map(|log| log.as_bytes())
+            .unwrap_or(&[]);
+        let buf_size = args[1].to_u64() as i64;
+        let length = args[2].to_u64() as usize as *mut types::GLsizei;
+        let info_log = args[3].to_u64() as usize as *mut u8;
+
+        if buf_size <= 0 || info_log.is_null() {
+            return;
+        }
+        let written = log.len().min(buf_size as usize - 1);
+        ptr::copy_nonoverlapping(log.as_ptr(), info_log, written);
+        *info_log.add(written) = 0;
+        if !length.is_null() {
+            *length = written as types::GLsizei;
+        }
+    }
+}
+
+/// Returns the object of the function named `prefix` followed by a suffix of `OBJECT_FUNCTIONS`.
+fn object_of(name: &str, prefix: &str) -> Option<Object> {
+    if !name.starts_with(prefix) {
+        return None;
+    }
+    OBJECT_FUNCTIONS.iter()
+        .find(|&&(suffix, _)| &name[prefix.len()..] == suffix)
+        .map(|&(_, object)| object)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Mock, Object};
+    use bindings;
+    use std::ptr;
+    use trace::Arg;
+
+    #[test]
+    fn created_objects_should_be_alive_until_deleted() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        let mut buffers = [0; 2];
+        let program = unsafe {
+            gl.GenBuffers(2, buffers.as_mut_ptr());
+            gl.CreateProgram()
+        };
+        assert_eq!(vec![(Object::Buffer, 1), (Object::Buffer, 2), (Object::Program, 3)], mock.live_objects());
+
+        unsafe {
+            gl.DeleteBuffers(2, buffers.as_ptr());
+            gl.DeleteProgram(program);
+            gl.DeleteProgram(program);
+        }
+        assert!(mock.live_objects().is_empty());
+        assert_eq!(vec![(Object::Program, 3)], mock.invalid_deletes());
+    }
+
+    #[test]
+    fn calls_should_be_recorded() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        unsafe {
+            gl.BufferData(bindings::ARRAY_BUFFER, 3, [1u8, 2, 3].as_ptr() as *const _, bindings::STATIC_DRAW);
+            gl.Viewport(0, 0, 640, 480);
+        }
+
+        let calls = mock.calls_to("BufferData");
+        assert_eq!(1, calls.len());
+        assert_eq!(Arg::Payload(vec![1, 2, 3]), calls[0].args[2]);
+        assert_eq!("glViewport(0, 0, 640, 480)", mock.calls_to("Viewport")[0].to_string());
+    }
+
+    #[test]
+    fn injected_link_failure_should_be_reported_with_log() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        mock.fail_next_link("missing main");
+
+        let mut status = -1;
+        let mut len = -1;
+        let mut log = [b' '; 32];
+        unsafe {
+            let program = gl.CreateProgram();
+            gl.LinkProgram(program);
+            gl.GetProgramiv(program, bindings::LINK_STATUS, &mut status);
+            gl.GetProgramiv(program, bindings::INFO_LOG_LENGTH, &mut len);
+            gl.GetProgramInfoLog(program, len, ptr::null_mut(), log.as_mut_ptr() as *mut _);
+        }
+
+        assert_eq!(bindings::FALSE as i32, status);
+        assert_eq!(13, len);
+        assert_eq!(b"missing main\0", &log[..13]);
+    }
+
+    #[test]
+    fn injected_errors_should_be_returned_in_order() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+        mock.push_error(bindings::INVALID_ENUM);
+        mock.push_error(bindings::INVALID_VALUE);
+
+        unsafe {
+            assert_eq!(bindings::INVALID_ENUM, gl.GetError());
+            assert_eq!(bindings::INVALID_VALUE, gl.GetError());
+            assert_eq!(bindings::NO_ERROR, gl.GetError());
+        }
+    }
+}
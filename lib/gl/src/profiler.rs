@@ -0,0 +1,52 @@
+use bindings::{FN_NAMES, PROFILER_FN_CALLS, PROFILER_FN_ERRORS, PROFILER_FN_NANOS};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Calls of a GL function since the last `profiler_reset`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FnStats {
+    /// Function name without the `gl` prefix, the same as the `Gl` method.
+    pub name: &'static str,
+    pub calls: usize,
+    /// Calls after which `glGetError` returned an error.
+    pub errors: usize,
+    /// Time spent in the function, zero unless the `debug_timing` feature is enabled.
+    pub time: Duration,
+}
+
+/// Clears the call, error and time counters of all functions.
+pub fn profiler_reset() {
+    for index in 0..FN_NAMES.len() {
+        PROFILER_FN_CALLS[index].store(0, Ordering::Relaxed);
+        PROFILER_FN_ERRORS[index].store(0, Ordering::Relaxed);
+        PROFILER_FN_NANOS[index].store(0, Ordering::Relaxed);
+    }
+}
+
+/// Returns the number of GL calls since the last `profiler_reset`.
+pub fn profiler_call_count() -> usize {
+    PROFILER_FN_CALLS.iter().map(|calls| calls.load(Ordering::Relaxed)).sum()
+}
+
+/// Returns the number of GL calls that produced an error since the last `profiler_reset`.
+pub fn profiler_err_count() -> usize {
+    PROFILER_FN_ERRORS.iter().map(|errors| errors.load(Ordering::Relaxed)).sum()
+}
+
+/// Returns the functions called since the last `profiler_reset`, the most called first.
+pub fn profiler_fn_stats() -> Vec<FnStats> {
+    let mut stats: Vec<_> = (0..FN_NAMES.len())
+        .map(|index| {
+            let nanos = PROFILER_FN_NANOS[index].load(Ordering::Relaxed);
+            FnStats {
+                name: FN_NAMES[index],
+                calls: PROFILER_FN_CALLS[index].load(Ordering::Relaxed),
+                errors: PROFILER_FN_ERRORS[index].load(Ordering::Relaxed),
+                time: Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32),
+            }
+        })
+        .filter(|stats| stats.calls > 0)
+        .collect();
+    stats.sort_by(|a, b| b.calls.cmp(&a.calls).then(b.time.cmp(&a.time)));
+    stats
+}
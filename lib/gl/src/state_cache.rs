@@ -0,0 +1,215 @@
+//! Skips calls that would not change the GL state.
+//!
+//! `Gl` remembers bound buffers, the vertex array, the program, enabled capabilities and the
+//! blend function, and these methods of `Gl` return without calling GL if the value is already
+//! set. Only calls made through these methods update the cache, so code that changes the state
+//! in other ways, for example through `InnerGl` or another library that shares the context,
+//! has to call `Gl::invalidate_state` after it.
+
+use bindings::types::{GLenum, GLintptr, GLsizei, GLsizeiptr, GLuint};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use Gl;
+
+static SAVED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of calls skipped since the last `state_cache_reset_count`.
+pub fn state_cache_saved_count() -> usize {
+    SAVED_CALLS.load(Ordering::Relaxed)
+}
+
+pub fn state_cache_reset_count() {
+    SAVED_CALLS.store(0, Ordering::Relaxed);
+}
+
+/// Values set by the last calls, a missing value is unknown.
+#[derive(Default)]
+pub(crate) struct StateCache {
+    buffers: RefCell<HashMap<GLenum, GLuint>>,
+    vertex_array: Cell<Option<GLuint>>,
+    program: Cell<Option<GLuint>>,
+    capabilities: RefCell<HashMap<GLenum, bool>>,
+    blend_func: Cell<Option<(GLenum, GLenum)>>,
+}
+
+impl StateCache {
+    fn clear(&self) {
+        self.buffers.borrow_mut().clear();
+        self.vertex_array.set(None);
+        self.program.set(None);
+        self.capabilities.borrow_mut().clear();
+        self.blend_func.set(None);
+    }
+}
+
+/// Remembers the value, returns `false` and counts the saved call if it was already set.
+fn update<T: Copy + PartialEq>(cached: &Cell<Option<T>>, value: T) -> bool {
+    if cached.get() == Some(value) {
+        SAVED_CALLS.fetch_add(1, Ordering::Relaxed);
+        return false;
+    }
+    cached.set(Some(value));
+    true
+}
+
+/// Same as `update`, for values cached per key.
+fn update_key<K: Eq + Hash, V: PartialEq>(cached: &RefCell<HashMap<K, V>>, key: K, value: V) -> bool {
+    let mut cached = cached.borrow_mut();
+    if cached.get(&key) == Some(&value) {
+        SAVED_CALLS.fetch_add(1, Ordering::Relaxed);
+        return false;
+    }
+    cached.insert(key, value);
+    true
+}
+
+/// Forgets the value if it is one of the deleted names.
+unsafe fn forget_deleted(cached: &Cell<Option<GLuint>>, n: GLsizei, names: *const GLuint) {
+    if let Some(name) = cached.get() {
+        if is_deleted(name, n, names) {
+            cached.set(None);
+        }
+    }
+}
+
+unsafe fn is_deleted(name: GLuint, n: GLsizei, names: *const GLuint) -> bool {
+    !names.is_null() && (0..n as isize).any(|i| *names.offset(i) == name)
+}
+
+#[allow(non_snake_case)]
+impl Gl {
+    /// Forgets the cached state, so that the next calls are not skipped.
+    ///
+    /// Call this after the state was changed without this `Gl`.
+    pub fn invalidate_state(&self) {
+        self.state.clear();
+    }
+
+    pub unsafe fn BindBuffer(&self, target: GLenum, buffer: GLuint) {
+        if update_key(&self.state.buffers, target, buffer) {
+            self.inner.BindBuffer(target, buffer);
+        }
+    }
+
+    /// Never skipped, also sets the buffer bound to the target.
+    pub unsafe fn BindBufferBase(&self, target: GLenum, index: GLuint, buffer: GLuint) {
+        self.state.buffers.borrow_mut().insert(target, buffer);
+        self.inner.BindBufferBase(target, index, buffer);
+    }
+
+    /// Never skipped, also sets the buffer bound to the target.
+    pub unsafe fn BindBufferRange(&self, target: GLenum, index: GLuint, buffer: GLuint, offset: GLintptr, size: GLsizeiptr) {
+        self.state.buffers.borrow_mut().insert(target, buffer);
+        self.inner.BindBufferRange(target, index, buffer, offset, size);
+    }
+
+    pub unsafe fn DeleteBuffers(&self, n: GLsizei, buffers: *const GLuint) {
+        // deleted buffers are unbound, and their names can be reused
+        self.state.buffers.borrow_mut().retain(|_, &mut buffer| !is_deleted(buffer, n, buffers));
+        self.inner.DeleteBuffers(n, buffers);
+    }
+
+    pub unsafe fn BindVertexArray(&self, array: GLuint) {
+        if update(&self.state.vertex_array, array) {
+            // the element array buffer binding is part of the vertex array
+            self.state.buffers.borrow_mut().remove(&::bindings::ELEMENT_ARRAY_BUFFER);
+            self.inner.BindVertexArray(array);
+        }
+    }
+
+    pub unsafe fn DeleteVertexArrays(&self, n: GLsizei, arrays: *const GLuint) {
+        if self.state.vertex_array.get().map_or(false, |array| is_deleted(array, n, arrays)) {
+            self.state.vertex_array.set(None);
+            self.state.buffers.borrow_mut().remove(&::bindings::ELEMENT_ARRAY_BUFFER);
+        }
+        self.inner.DeleteVertexArrays(n, arrays);
+    }
+
+    pub unsafe fn UseProgram(&self, program: GLuint) {
+        if update(&self.state.program, program) {
+            self.inner.UseProgram(program);
+        }
+    }
+
+    pub unsafe fn DeleteProgram(&self, program: GLuint) {
+        forget_deleted(&self.state.program, 1, &program);
+        self.inner.DeleteProgram(program);
+    }
+
+    pub unsafe fn Enable(&self, cap: GLenum) {
+        if update_key(&self.state.capabilities, cap, true) {
+            self.inner.Enable(cap);
+        }
+    }
+
+    pub unsafe fn Disable(&self, cap: GLenum) {
+        if update_key(&self.state.capabilities, cap, false) {
+            self.inner.Disable(cap);
+        }
+    }
+
+    pub unsafe fn BlendFunc(&self, sfactor: GLenum, dfactor: GLenum) {
+        if update(&self.state.blend_func, (sfactor, dfactor)) {
+            self.inner.BlendFunc(sfactor, dfactor);
+        }
+    }
+
+    /// Never skipped, makes the blend function unknown.
+    pub unsafe fn BlendFuncSeparate(&self, sfactor_rgb: GLenum, dfactor_rgb: GLenum, sfactor_alpha: GLenum, dfactor_alpha: GLenum) {
+        self.state.blend_func.set(None);
+        self.inner.BlendFuncSeparate(sfactor_rgb, dfactor_rgb, sfactor_alpha, dfactor_alpha);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod test {
+    use bindings;
+    use mock::Mock;
+
+    #[test]
+    fn repeated_calls_should_be_skipped() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        unsafe {
+            gl.BindBuffer(bindings::ARRAY_BUFFER, 1);
+            gl.BindBuffer(bindings::ARRAY_BUFFER, 1);
+            gl.BindBuffer(bindings::ELEMENT_ARRAY_BUFFER, 1);
+            gl.Enable(bindings::BLEND);
+            gl.Enable(bindings::BLEND);
+            gl.Disable(bindings::BLEND);
+            gl.UseProgram(3);
+            gl.UseProgram(3);
+        }
+
+        assert_eq!(2, mock.calls_to("BindBuffer").len());
+        assert_eq!(1, mock.calls_to("Enable").len());
+        assert_eq!(1, mock.calls_to("Disable").len());
+        assert_eq!(1, mock.calls_to("UseProgram").len());
+    }
+
+    #[test]
+    fn deleted_and_invalidated_state_should_be_set_again() {
+        let mock = Mock::new();
+        let gl = mock.gl();
+
+        unsafe {
+            gl.BindBuffer(bindings::ARRAY_BUFFER, 1);
+            gl.DeleteBuffers(1, &1);
+            gl.BindBuffer(bindings::ARRAY_BUFFER, 1);
+
+            gl.BindBuffer(bindings::ELEMENT_ARRAY_BUFFER, 2);
+            gl.BindVertexArray(4);
+            gl.BindBuffer(bindings::ELEMENT_ARRAY_BUFFER, 2);
+
+            gl.BlendFunc(bindings::ONE, bindings::ZERO);
+            gl.invalidate_state();
+            gl.BlendFunc(bindings::ONE, bindings::ZERO);
+        }
+
+        assert_eq!(4, mock.calls_to("BindBuffer").len());
+        assert_eq!(2, mock.calls_to("BlendFunc").len());
+    }
+}
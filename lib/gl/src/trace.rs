@@ -0,0 +1,592 @@
+//! Recording of GL calls into a binary trace, and replay of recorded traces.
+//!
+//! Calls are recorded with their arguments. Pointer arguments are recorded as addresses, except
+//! the contents that are uploaded to GL, which are recorded as payloads: buffer data, uniform
+//! values, texture pixels, shader sources and names of attributes and uniforms. Only the calls
+//! of the thread that started the recording are recorded, as GL contexts are per thread.
+//!
+//! Pixel sizes assume that only `UNPACK_ALIGNMENT` of the pixel storage modes is changed, and
+//! that it is changed during the recording. While a buffer is bound to `PIXEL_UNPACK_BUFFER`,
+//! pixels are recorded as buffer offsets, and offsets of `0x10000` and more are not replayed.
+//!
+//! Object names returned by `Gen*` and `Create*` functions are not remapped, so replay assumes a
+//! fresh context that hands out the same names as the recorded one.
+
+use bindings::{self, FN_NAMES};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::ptr;
+use std::slice;
+
+const MAGIC: &[u8; 4] = b"GLTR";
+const VERSION: u8 = 1;
+
+/// Pointers below this value are replayed as they are, because they are offsets into bound buffers.
+const MIN_REPLAYED_ADDRESS: u64 = 0x10000;
+/// Size of the zeroed memory that replaces other recorded pointers during replay.
+const REPLAY_SCRATCH_LEN: usize = 64 * 1024 * 1024;
+
+/// Argument of the recorded call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Int(i64),
+    Uint(u64),
+    F32(f32),
+    F64(f64),
+    /// Address or buffer offset, the contents are not recorded.
+    Pointer(u64),
+    /// Callback, `true` if it was not null.
+    Callback(bool),
+    /// Contents uploaded by the call.
+    Payload(Vec<u8>),
+}
+
+impl Arg {
+    /// Copies `len` bytes at `data`, used by the generated bindings.
+    ///
+    /// Null pointers, buffer offsets and unknown lengths are recorded as pointers.
+    pub(crate) unsafe fn payload(data: *const u8, len: isize) -> Arg {
+        if (data as usize as u64) < MIN_REPLAYED_ADDRESS || len < 0 {
+            return Arg::Pointer(data as usize as u64);
+        }
+        Arg::Payload(slice::from_raw_parts(data, len as usize).to_vec())
+    }
+
+    /// Copies the pixels of `glTexImage*` and similar functions, used by the generated bindings.
+    ///
+    /// While a buffer is bound to `PIXEL_UNPACK_BUFFER`, `data` is an offset into it and is
+    /// recorded as a pointer.
+    pub(crate) unsafe fn pixels(data: *const u8, len: isize) -> Arg {
+        let unpack_buffer = RECORDER.with(|recorder| {
+            recorder.borrow().as_ref().map(|recorder| recorder.pixel_unpack_buffer).unwrap_or(0)
+        });
+        if unpack_buffer != 0 {
+            return Arg::Pointer(data as usize as u64);
+        }
+        Arg::payload(data, len)
+    }
+
+    /// Copies `count` strings of `glShaderSource` into one payload, `lengths` are the lengths of the
+    /// strings, or null if the strings are null-terminated.
+    pub(crate) unsafe fn strings(strings: *const *const u8, count: isize, lengths: *const i32) -> Arg {
+        if strings.is_null() || count < 0 {
+            return Arg::Pointer(strings as usize as u64);
+        }
+        let mut data = Vec::new();
+        for i in 0..count {
+            let string = *strings.offset(i);
+            let len = match lengths.is_null() {
+                false if *lengths.offset(i) >= 0 => *lengths.offset(i) as usize,
+                _ => CStr::from_ptr(string as *const _).to_bytes().len(),
+            };
+            data.extend_from_slice(slice::from_raw_parts(string, len));
+        }
+        Arg::Payload(data)
+    }
+
+    /// Copies the null-terminated string, including the terminator.
+    pub(crate) unsafe fn c_string(string: *const u8) -> Arg {
+        if string.is_null() {
+            return Arg::Pointer(0);
+        }
+        Arg::Payload(CStr::from_ptr(string as *const _).to_bytes_with_nul().to_vec())
+    }
+
+    pub(crate) fn payload_len(&self) -> usize {
+        match *self {
+            Arg::Payload(ref data) => data.len(),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn to_u64(&self) -> u64 {
+        match *self {
+            Arg::Int(value) => value as u64,
+            Arg::Uint(value) | Arg::Pointer(value) => value,
+            Arg::F32(value) => value as u64,
+            Arg::F64(value) => value as u64,
+            Arg::Callback(_) | Arg::Payload(_) => 0,
+        }
+    }
+
+    pub(crate) fn to_f64(&self) -> f64 {
+        match *self {
+            Arg::F32(value) => value as f64,
+            Arg::F64(value) => value,
+            Arg::Int(value) => value as f64,
+            ref other => other.to_u64() as f64,
+        }
+    }
+
+    /// Returns the pointer to pass to the replayed call.
+    ///
+    /// Payloads point to the recorded contents, null pointers and offsets stay the same, and other
+    /// addresses point to zeroed scratch memory.
+    pub(crate) fn to_ptr(&self, scratch: &mut Vec<u8>) -> *mut u8 {
+        match *self {
+            Arg::Payload(ref data) => data.as_ptr() as *mut u8,
+            Arg::Pointer(value) if value < MIN_REPLAYED_ADDRESS => value as usize as *mut u8,
+            Arg::Pointer(_) => {
+                if scratch.is_empty() {
+                    scratch.resize(REPLAY_SCRATCH_LEN, 0);
+                }
+                scratch.as_mut_ptr()
+            },
+            _ => ptr::null_mut(),
+        }
+    }
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Arg::Int(value) => write!(f, "{}", value),
+            Arg::Uint(value) => write!(f, "{}", value),
+            Arg::F32(value) => write!(f, "{:?}", value),
+            Arg::F64(value) => write!(f, "{:?}", value),
+            Arg::Pointer(0) => write!(f, "null"),
+            Arg::Pointer(value) => write!(f, "0x{:x}", value),
+            Arg::Callback(true) => write!(f, "<callback>"),
+            Arg::Callback(false) => write!(f, "null"),
+            Arg::Payload(ref data) => write!(f, "<{} bytes>", data.len()),
+        }
+    }
+}
+
+/// Recorded call, the name is without the `gl` prefix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "gl{}(", self.name)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", arg)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Calls read from the trace file, displayed as one call per line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trace {
+    pub calls: Vec<Call>,
+}
+
+impl Trace {
+    pub fn read(input: &mut Read) -> io::Result<Trace> {
+        let mut header = [0; 5];
+        input.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(invalid_data("not a GL trace or unsupported version"));
+        }
+
+        let mut names = Vec::new();
+        for _ in 0..read_u32(input)? {
+            let len = read_u16(input)? as usize;
+            let mut name = vec![0; len];
+            input.read_exact(&mut name)?;
+            names.push(String::from_utf8(name).map_err(|_| invalid_data("function name is not UTF-8"))?);
+        }
+
+        let mut calls = Vec::new();
+        let mut index = [0; 2];
+        loop {
+            match input.read(&mut index[..1])? {
+                0 => break,
+                _ => input.read_exact(&mut index[1..])?,
+            }
+            let name = names.get(u16::from_le_bytes(index) as usize)
+                .ok_or_else(|| invalid_data("unknown function index"))?
+                .clone();
+            let mut args = Vec::new();
+            for _ in 0..read_u8(input)? {
+                args.push(read_arg(input)?);
+            }
+            calls.push(Call { name, args });
+        }
+
+        Ok(Trace { calls })
+    }
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<Trace> {
+        Trace::read(&mut io::BufReader::new(File::open(path)?))
+    }
+
+    /// Issues the calls again, returns the number of calls skipped because `gl` does not have
+    /// the function or it has different parameters.
+    ///
+    /// Pointers that were neither payloads nor buffer offsets point to zeroed memory, so calls
+    /// that read other client memory are replayed with zeros.
+    pub unsafe fn replay(&self, gl: &bindings::Gl) -> usize {
+        let indices: HashMap<&str, usize> = FN_NAMES.iter().enumerate()
+            .map(|(index, name)| (*name, index))
+            .collect();
+        let mut scratch = Vec::new();
+        let mut skipped = 0;
+
+        for call in &self.calls {
+            let replayed = match indices.get(&call.name[..]) {
+                Some(&index) => bindings::trace_replay_call(gl, index, &call.args, &mut scratch),
+                None => false,
+            };
+            if !replayed {
+                skipped += 1;
+            }
+        }
+
+        skipped
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for call in &self.calls {
+            writeln!(f, "{}", call)?;
+        }
+        Ok(())
+    }
+}
+
+struct Recorder {
+    output: Box<Write>,
+    error: Option<io::Error>,
+    /// The last `UNPACK_ALIGNMENT` set by `glPixelStorei`.
+    unpack_alignment: usize,
+    /// The buffer bound to `PIXEL_UNPACK_BUFFER` by `glBindBuffer`, or 0.
+    pixel_unpack_buffer: bindings::types::GLuint,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = RefCell::new(None);
+}
+
+/// Starts recording GL calls of this thread into the output, replacing the previous recording.
+pub fn start(mut output: Box<Write>) -> io::Result<()> {
+    output.write_all(MAGIC)?;
+    output.write_all(&[VERSION])?;
+    output.write_all(&(FN_NAMES.len() as u32).to_le_bytes())?;
+    for name in FN_NAMES.iter() {
+        output.write_all(&(name.len() as u16).to_le_bytes())?;
+        output.write_all(name.as_bytes())?;
+    }
+
+    RECORDER.with(|recorder| *recorder.borrow_mut() = Some(Recorder {
+        output,
+        error: None,
+        unpack_alignment: 4,
+        pixel_unpack_buffer: 0,
+    }));
+    Ok(())
+}
+
+pub fn start_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    start(Box::new(BufWriter::new(File::create(path)?)))
+}
+
+/// Stops recording and flushes the output, returns the first error of the recording.
+pub fn stop() -> io::Result<()> {
+    match RECORDER.with(|recorder| recorder.borrow_mut().take()) {
+        Some(Recorder { error: Some(e), .. }) => Err(e),
+        Some(mut recorder) => recorder.output.flush(),
+        None => Ok(()),
+    }
+}
+
+pub fn is_recording() -> bool {
+    RECORDER.with(|recorder| recorder.borrow().is_some())
+}
+
+/// Writes the call of the function at `index`, used by the generated bindings.
+pub(crate) fn record(index: usize, args: &[Arg]) {
+    RECORDER.with(|recorder| {
+        if let Some(ref mut recorder) = *recorder.borrow_mut() {
+            if recorder.error.is_some() {
+                return;
+            }
+            let mut data = Vec::with_capacity(3 + args.len() * 9);
+            data.extend_from_slice(&(index as u16).to_le_bytes());
+            data.push(args.len() as u8);
+            for arg in args {
+                write_arg(&mut data, arg);
+            }
+            if let Err(e) = recorder.output.write_all(&data) {
+                recorder.error = Some(e);
+            }
+        }
+    });
+}
+
+/// Remembers the pixel storage mode set by `glPixelStorei`, used by the generated bindings.
+pub(crate) fn pixel_store(pname: bindings::types::GLenum, param: bindings::types::GLint) {
+    RECORDER.with(|recorder| {
+        if let Some(ref mut recorder) = *recorder.borrow_mut() {
+            if pname == bindings::UNPACK_ALIGNMENT && param > 0 {
+                recorder.unpack_alignment = param as usize;
+            }
+        }
+    });
+}
+
+/// Remembers the buffer bound to `PIXEL_UNPACK_BUFFER`, used by the generated bindings.
+pub(crate) fn bind_buffer(target: bindings::types::GLenum, buffer: bindings::types::GLuint) {
+    RECORDER.with(|recorder| {
+        if let Some(ref mut recorder) = *recorder.borrow_mut() {
+            if target == bindings::PIXEL_UNPACK_BUFFER {
+                recorder.pixel_unpack_buffer = buffer;
+            }
+        }
+    });
+}
+
+/// Forgets the `PIXEL_UNPACK_BUFFER` binding if the bound buffer is deleted, used by the generated
+/// bindings.
+pub(crate) unsafe fn delete_buffers(n: bindings::types::GLsizei, buffers: *const bindings::types::GLuint) {
+    if buffers.is_null() || n <= 0 {
+        return;
+    }
+    let buffers = slice::from_raw_parts(buffers, n as usize);
+    RECORDER.with(|recorder| {
+        if let Some(ref mut recorder) = *recorder.borrow_mut() {
+            if buffers.contains(&recorder.pixel_unpack_buffer) {
+                recorder.pixel_unpack_buffer = 0;
+            }
+        }
+    });
+}
+
+/// Returns the size in bytes of the pixels uploaded by `glTexImage*` and `glTexSubImage*`,
+/// or -1 if the format or type is not known. Used by the generated bindings.
+pub(crate) fn pixels_len(format: bindings::types::GLenum, type_: bindings::types::GLenum, width: i32, height: i32, depth: i32) -> isize {
+    let pixel_size = match pixel_size(format, type_) {
+        Some(pixel_size) => pixel_size,
+        None => return -1,
+    };
+    if width <= 0 || height <= 0 || depth <= 0 {
+        return 0;
+    }
+    let alignment = RECORDER.with(|recorder| {
+        recorder.borrow().as_ref().map(|recorder| recorder.unpack_alignment).unwrap_or(4)
+    });
+
+    let row = width as usize * pixel_size;
+    let stride = (row + alignment - 1) / alignment * alignment;
+    (stride * (height as usize * depth as usize - 1) + row) as isize
+}
+
+/// Returns the size in bytes of one pixel of the format and type.
+///
+/// Values are used instead of constants, as not all of them exist in every API.
+fn pixel_size(format: u32, type_: u32) -> Option<usize> {
+    let components = match format {
+        // RED, GREEN, BLUE, ALPHA, LUMINANCE, DEPTH_COMPONENT, STENCIL_INDEX, RED_INTEGER
+        0x1903 | 0x1904 | 0x1905 | 0x1906 | 0x1909 | 0x1902 | 0x1901 | 0x8D94 => 1,
+        // RG, LUMINANCE_ALPHA, DEPTH_STENCIL, RG_INTEGER
+        0x8227 | 0x190A | 0x84F9 | 0x8228 => 2,
+        // RGB, BGR, RGB_INTEGER, BGR_INTEGER
+        0x1907 | 0x80E0 | 0x8D98 | 0x8D9A => 3,
+        // RGBA, BGRA, RGBA_INTEGER, BGRA_INTEGER
+        0x1908 | 0x80E1 | 0x8D99 | 0x8D9B => 4,
+        _ => return None,
+    };
+    Some(match type_ {
+        // UNSIGNED_BYTE, BYTE
+        0x1401 | 0x1400 => components,
+        // UNSIGNED_SHORT, SHORT, HALF_FLOAT, HALF_FLOAT_OES
+        0x1403 | 0x1402 | 0x140B | 0x8D61 => components * 2,
+        // UNSIGNED_INT, INT, FLOAT
+        0x1405 | 0x1404 | 0x1406 => components * 4,
+        // UNSIGNED_BYTE_3_3_2, UNSIGNED_BYTE_2_3_3_REV
+        0x8032 | 0x8362 => 1,
+        // UNSIGNED_SHORT_4_4_4_4, _5_5_5_1, _5_6_5, _5_6_5_REV, _4_4_4_4_REV, _1_5_5_5_REV
+        0x8033 | 0x8034 | 0x8363 | 0x8364 | 0x8365 | 0x8366 => 2,
+        // UNSIGNED_INT_8_8_8_8, _10_10_10_2, _8_8_8_8_REV, _2_10_10_10_REV, _24_8,
+        // _10F_11F_11F_REV, _5_9_9_9_REV
+        0x8035 | 0x8036 | 0x8367 | 0x8368 | 0x84FA | 0x8C3B | 0x8C3E => 4,
+        // FLOAT_32_UNSIGNED_INT_24_8_REV
+        0x8DAD => 8,
+        _ => return None,
+    })
+}
+
+fn write_arg(data: &mut Vec<u8>, arg: &Arg) {
+    match *arg {
+        Arg::Int(value) => { data.push(0); data.extend_from_slice(&value.to_le_bytes()); },
+        Arg::Uint(value) => { data.push(1); data.extend_from_slice(&value.to_le_bytes()); },
+        Arg::F32(value) => { data.push(2); data.extend_from_slice(&value.to_bits().to_le_bytes()); },
+        Arg::F64(value) => { data.push(3); data.extend_from_slice(&value.to_bits().to_le_bytes()); },
+        Arg::Pointer(value) => { data.push(4); data.extend_from_slice(&value.to_le_bytes()); },
+        Arg::Callback(is_set) => { data.push(5); data.push(is_set as u8); },
+        Arg::Payload(ref payload) => {
+            data.push(6);
+            data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            data.extend_from_slice(payload);
+        },
+    }
+}
+
+fn read_arg(input: &mut Read) -> io::Result<Arg> {
+    Ok(match read_u8(input)? {
+        0 => Arg::Int(read_u64(input)? as i64),
+        1 => Arg::Uint(read_u64(input)?),
+        2 => Arg::F32(f32::from_bits(read_u32(input)?)),
+        3 => Arg::F64(f64::from_bits(read_u64(input)?)),
+        4 => Arg::Pointer(read_u64(input)?),
+        5 => Arg::Callback(read_u8(input)? != 0),
+        6 => {
+            // the length is not trusted, the payload only grows as its contents are read
+            let len = read_u32(input)? as u64;
+            let mut payload = Vec::new();
+            input.take(len).read_to_end(&mut payload)?;
+            if payload.len() as u64 != len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "payload is truncated"));
+            }
+            Arg::Payload(payload)
+        },
+        _ => return Err(invalid_data("unknown argument kind")),
+    })
+}
+
+fn read_u8(input: &mut Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16(input: &mut Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    input.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &mut Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{start, stop, record, pixel_store, bind_buffer, delete_buffers, pixels_len, Arg, Call, Trace};
+    use bindings;
+    use bindings::FN_NAMES;
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    /// Output that can be read after the recording stops.
+    #[derive(Clone)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorded_calls_should_be_read_back_and_printed() {
+        let index = FN_NAMES.iter().position(|name| *name == "BufferData").unwrap();
+        let output = Shared(Arc::new(Mutex::new(Vec::new())));
+
+        start(Box::new(output.clone())).unwrap();
+        record(index, &[Arg::Uint(34962), Arg::Int(3), Arg::Payload(vec![1, 2, 3]), Arg::Uint(35044)]);
+        stop().unwrap();
+        record(index, &[]);
+
+        let data = output.0.lock().unwrap().clone();
+        let trace = Trace::read(&mut &data[..]).unwrap();
+
+        assert_eq!(
+            vec![Call {
+                name: String::from("BufferData"),
+                args: vec![Arg::Uint(34962), Arg::Int(3), Arg::Payload(vec![1, 2, 3]), Arg::Uint(35044)],
+            }],
+            trace.calls
+        );
+        assert_eq!("glBufferData(34962, 3, <3 bytes>, 35044)\n", trace.to_string());
+    }
+
+    #[test]
+    fn shader_sources_and_names_should_be_recorded_as_payloads() {
+        let parts = [b"void ".as_ptr(), b"main() {}\0".as_ptr()];
+        let lengths = [5, -1];
+
+        unsafe {
+            assert_eq!(Arg::Payload(b"void main() {}".to_vec()), Arg::strings(parts.as_ptr(), 2, lengths.as_ptr()));
+            assert_eq!(Arg::Payload(b"main() {}".to_vec()), Arg::strings(parts[1..].as_ptr(), 1, ::std::ptr::null()));
+            assert_eq!(Arg::Payload(b"Position\0".to_vec()), Arg::c_string(b"Position\0".as_ptr()));
+            assert_eq!(Arg::Pointer(16), Arg::payload(16 as *const u8, 4));
+        }
+    }
+
+    #[test]
+    fn pixels_len_should_follow_unpack_alignment() {
+        start(Box::new(io::sink())).unwrap();
+        assert_eq!(4 * 3 + 3, pixels_len(bindings::RGB, bindings::UNSIGNED_BYTE, 1, 4, 1));
+        pixel_store(bindings::UNPACK_ALIGNMENT, 1);
+        assert_eq!(3 * 4, pixels_len(bindings::RGB, bindings::UNSIGNED_BYTE, 1, 4, 1));
+        assert_eq!(2 * 2 * 2 * 16, pixels_len(bindings::RGBA, bindings::FLOAT, 2, 2, 2));
+        assert_eq!(-1, pixels_len(0, bindings::FLOAT, 2, 2, 2));
+        stop().unwrap();
+    }
+
+    #[test]
+    fn pixels_should_be_recorded_as_offsets_while_unpack_buffer_is_bound() {
+        let pixels = [0u8; 0x20000];
+        let offset = 0x10000 as *const u8;
+
+        start(Box::new(io::sink())).unwrap();
+        unsafe {
+            assert_eq!(Arg::Payload(vec![0; 16]), Arg::pixels(pixels.as_ptr(), 16));
+
+            bind_buffer(bindings::ARRAY_BUFFER, 3);
+            assert_eq!(Arg::Payload(vec![0; 16]), Arg::pixels(pixels.as_ptr(), 16));
+
+            bind_buffer(bindings::PIXEL_UNPACK_BUFFER, 3);
+            assert_eq!(Arg::Pointer(0x10000), Arg::pixels(offset, 16));
+
+            delete_buffers(1, [3].as_ptr());
+            assert_eq!(Arg::Payload(vec![0; 16]), Arg::pixels(pixels.as_ptr(), 16));
+
+            bind_buffer(bindings::PIXEL_UNPACK_BUFFER, 4);
+            bind_buffer(bindings::PIXEL_UNPACK_BUFFER, 0);
+            assert_eq!(Arg::Payload(vec![0; 16]), Arg::pixels(pixels.as_ptr(), 16));
+        }
+        stop().unwrap();
+    }
+
+    #[test]
+    fn other_data_should_not_be_read_as_trace() {
+        assert!(Trace::read(&mut &b"GLTR\x02"[..]).is_err());
+        assert!(Trace::read(&mut &b"trace"[..]).is_err());
+        // a BufferData call whose payload claims 4 GiB
+        let mut truncated = b"GLTR\x01\x01\x00\x00\x00\x01\x00A\x00\x00\x01\x06\xff\xff\xff\xff".to_vec();
+        truncated.extend_from_slice(b"data");
+        assert_eq!(io::ErrorKind::UnexpectedEof, Trace::read(&mut &truncated[..]).unwrap_err().kind());
+    }
+}
@@ -0,0 +1,268 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::hash::BuildHasherDefault;
+use std::sync::{Arc, Weak, Mutex};
+use twox_hash::XxHash;
+use failure;
+use {Resources, Resource, ResourcePath, ResourcePathBuf, Error, Operation};
+use shared::resource_key;
+
+/// Type that can be decoded from resource contents.
+pub trait FromResource: Sized + Send + Sync + 'static {
+    /// Decodes the value, other resources (for example, relative to the path) can be loaded from `resources`.
+    fn from_resource(resources: &Resources, path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error>;
+}
+
+impl FromResource for Vec<u8> {
+    fn from_resource(_resources: &Resources, _path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+        Ok(data)
+    }
+}
+
+impl FromResource for String {
+    fn from_resource(_resources: &Resources, _path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+        Ok(String::from_utf8(data)?)
+    }
+}
+
+impl FromResource for CString {
+    fn from_resource(_resources: &Resources, _path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+        Ok(CString::new(data)?)
+    }
+}
+
+struct AssetState<T> {
+    resource: Resource,
+    value: Option<Arc<T>>,
+}
+
+/// Decoded value shared by all `Asset` handles of the same path and type.
+struct AssetSlot<T> {
+    path: ResourcePathBuf,
+    state: Mutex<AssetState<T>>,
+}
+
+/// Shared handle to a value decoded from the resource.
+///
+/// The value is decoded once for all handles of the same path and type, and is decoded again
+/// on the next `get` after the resource is modified.
+pub struct Asset<T> {
+    resources: Resources,
+    slot: Arc<AssetSlot<T>>,
+}
+
+impl<T> Clone for Asset<T> {
+    fn clone(&self) -> Self {
+        Asset {
+            resources: self.resources.clone(),
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T: FromResource> Asset<T> {
+    pub fn path(&self) -> &ResourcePath {
+        &self.slot.path
+    }
+
+    /// Check if the resource was modified after the value was last decoded.
+    pub fn is_modified(&self) -> bool {
+        self.slot.state.lock().expect("failed to lock asset")
+            .resource.is_modified()
+    }
+
+    /// Returns decoded value, decoding the resource again if it was modified.
+    ///
+    /// The lock is released while decoding, so the decoder can load other assets and other
+    /// handles are not blocked by slow decodes.
+    pub fn get(&self) -> Result<Arc<T>, Error> {
+        let (data, loader_id) = {
+            let state = self.slot.state.lock().expect("failed to lock asset");
+
+            if let Some(ref value) = state.value {
+                if !state.resource.is_modified() {
+                    return Ok(value.clone());
+                }
+            }
+
+            (state.resource.get()?, state.resource.serving_loader_id())
+        };
+
+        let value = Arc::new(
+            T::from_resource(&self.resources, &self.slot.path, data)
+                .map_err(|inner| {
+                    Error::FailedToDecode { path: self.slot.path.clone(), inner }
+                        .in_context(Operation::Read, &self.slot.path, loader_id.as_ref().map(|id| &id[..]))
+                })?
+        );
+        self.slot.state.lock().expect("failed to lock asset").value = Some(value.clone());
+
+        Ok(value)
+    }
+}
+
+/// Weak references to asset slots, so that a decoded value is dropped together with the last handle.
+pub struct AssetCache {
+    slots: HashMap<(TypeId, ResourcePathBuf), Weak<Any + Send + Sync>, BuildHasherDefault<XxHash>>,
+}
+
+impl AssetCache {
+    pub fn new() -> AssetCache {
+        AssetCache {
+            slots: HashMap::default(),
+        }
+    }
+
+    fn slot<T: FromResource>(&mut self, resources: &Resources, path: &ResourcePath) -> Arc<AssetSlot<T>> {
+        let key = (TypeId::of::<T>(), ResourcePathBuf::from(path));
+
+        if let Some(slot) = self.slots.get(&key).and_then(|slot| slot.upgrade()) {
+            if let Ok(slot) = slot.downcast::<AssetSlot<T>>() {
+                return slot;
+            }
+        }
+
+        self.slots.retain(|_, slot| slot.upgrade().is_some());
+
+        let slot = Arc::new(AssetSlot {
+            path: ResourcePathBuf::from(path),
+            state: Mutex::new(AssetState {
+                resource: resources.resource(path),
+                value: None,
+            }),
+        });
+        let any_slot: Arc<Any + Send + Sync> = slot.clone();
+        self.slots.insert(key, Arc::downgrade(&any_slot));
+
+        slot
+    }
+}
+
+pub fn load<T: FromResource>(resources: &Resources, cache: &Mutex<AssetCache>, path: &ResourcePath) -> Result<Asset<T>, Error> {
+    let clean_path = resource_key(path).map_err(|e| e.in_context(Operation::Read, path, None))?;
+    let slot = cache.lock().expect("failed to lock asset cache")
+        .slot::<T>(resources, &clean_path);
+
+    let asset = Asset {
+        resources: resources.clone(),
+        slot,
+    };
+    asset.get()?;
+
+    Ok(asset)
+}
+
+#[cfg(test)]
+mod test {
+    use {Resources, ResourcePath, Error, Operation};
+    use backend::InMemory;
+    use super::FromResource;
+    use std::cell::Cell;
+    use std::ffi::CString;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use failure;
+
+    static DECODE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(String);
+
+    impl FromResource for Counted {
+        fn from_resource(_resources: &Resources, _path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+            DECODE_COUNT.fetch_add(1, Ordering::SeqCst);
+            Ok(Counted(String::from_utf8(data)?))
+        }
+    }
+
+    #[test]
+    fn asset_should_be_decoded_once_per_path_and_again_after_modification() {
+        let res = Resources::new()
+            .loaded_from("a", 0, InMemory::new().with("counted", b"hello"));
+
+        let a = res.load::<Counted, _>("counted").unwrap();
+        let b = res.load::<Counted, _>("/counted").unwrap();
+
+        assert_eq!("hello", a.get().unwrap().0);
+        assert!(Arc::ptr_eq(&a.get().unwrap(), &b.get().unwrap()));
+        assert_eq!(1, DECODE_COUNT.load(Ordering::SeqCst));
+
+        res.resource("counted").write(b"world").unwrap();
+
+        assert!(a.is_modified());
+        assert_eq!("world", b.get().unwrap().0);
+        assert!(!a.is_modified());
+        assert_eq!("world", a.get().unwrap().0);
+        assert_eq!(2, DECODE_COUNT.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn assets_of_different_types_should_be_cached_separately() {
+        let res = Resources::new()
+            .loaded_from("a", 0, InMemory::new().with("name", b"hello"));
+
+        let string = res.load::<String, _>("name").unwrap();
+        let bytes = res.load::<Vec<u8>, _>("name").unwrap();
+
+        assert_eq!("hello", &string.get().unwrap()[..]);
+        assert_eq!(b"hello", &bytes.get().unwrap()[..]);
+    }
+
+    #[test]
+    fn decoded_value_should_be_dropped_with_the_last_handle() {
+        let res = Resources::new()
+            .loaded_from("a", 0, InMemory::new().with("name", b"hello"));
+
+        let value = res.load::<String, _>("name").unwrap().get().unwrap();
+
+        assert_eq!(1, Arc::strong_count(&value));
+    }
+
+    #[test]
+    fn loading_missing_or_invalid_resource_should_fail() {
+        let res = Resources::new()
+            .loaded_from("a", 0, InMemory::new().with("nul", b"hel\0lo"));
+
+        match res.load::<CString, _>("missing").map_err(Error::into_root) {
+            Err(Error::NotFound) => (),
+            _ => panic!("expected NotFound"),
+        }
+        match res.load::<CString, _>("nul") {
+            Err(ref e @ Error::Resource { .. }) => {
+                let context = e.resource_context().unwrap();
+                assert_eq!(Operation::Read, context.operation);
+                assert_eq!(Some("a"), context.loader_id.as_ref().map(|id| &id[..]));
+                match *e.root() {
+                    Error::FailedToDecode { .. } => (),
+                    _ => panic!("expected FailedToDecode"),
+                }
+            },
+            _ => panic!("expected FailedToDecode"),
+        }
+    }
+
+    thread_local!(static DECODING_NESTED: Cell<bool> = Cell::new(false));
+
+    struct Nested(String);
+
+    impl FromResource for Nested {
+        fn from_resource(resources: &Resources, path: &ResourcePath, data: Vec<u8>) -> Result<Self, failure::Error> {
+            // the first decode loads the same asset again, which must not wait for this decode
+            if DECODING_NESTED.with(|nested| nested.replace(true)) {
+                return Ok(Nested(String::from_utf8(data)?));
+            }
+            let inner = resources.load::<Nested, _>(path)?.get()?;
+            Ok(Nested(format!("outer {}", inner.0)))
+        }
+    }
+
+    #[test]
+    fn decoder_should_be_able_to_load_the_same_asset() {
+        let res = Resources::new()
+            .loaded_from("a", 0, InMemory::new().with("name", b"hello"));
+
+        let value = res.load::<Nested, _>("name").unwrap().get().unwrap();
+
+        assert_eq!("outer hello", &value.0[..]);
+    }
+}
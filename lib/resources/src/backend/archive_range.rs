@@ -0,0 +1,93 @@
+use backend::Archive;
+use std::cmp;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// Stream of an uncompressed entry that reads from the archive shared with the backend.
+pub struct ArchiveRange {
+    archive: Arc<Mutex<Box<Archive>>>,
+    start: u64,
+    len: u64,
+    position: u64,
+}
+
+impl ArchiveRange {
+    pub fn new(archive: Arc<Mutex<Box<Archive>>>, start: u64, len: u64) -> ArchiveRange {
+        ArchiveRange {
+            archive,
+            start,
+            len,
+            position: 0,
+        }
+    }
+}
+
+impl Read for ArchiveRange {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.position);
+        let count = cmp::min(buf.len() as u64, remaining) as usize;
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let mut archive = self.archive.lock().expect("failed to lock archive");
+        archive.seek(SeekFrom::Start(self.start + self.position))?;
+        let read = archive.read(&mut buf[..count])?;
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl Seek for ArchiveRange {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => add_offset(self.len, offset),
+            SeekFrom::Current(offset) => add_offset(self.position, offset),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+fn add_offset(base: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.wrapping_neg() as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArchiveRange;
+    use backend::Archive;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn range_should_read_and_seek_only_inside_the_entry() {
+        let archive: Box<Archive> = Box::new(Cursor::new(b"headerHELLO WORLDtrailer".to_vec()));
+        let mut range = ArchiveRange::new(Arc::new(Mutex::new(archive)), 6, 11);
+
+        let mut contents = String::new();
+        range.read_to_string(&mut contents).unwrap();
+        assert_eq!("HELLO WORLD", contents);
+
+        assert_eq!(6, range.seek(SeekFrom::End(-5)).unwrap());
+        let mut end = String::new();
+        range.read_to_string(&mut end).unwrap();
+        assert_eq!("WORLD", end);
+
+        assert!(range.seek(SeekFrom::Current(-20)).is_err());
+        assert_eq!(20, range.seek(SeekFrom::Start(20)).unwrap());
+        assert_eq!(0, range.read(&mut [0; 4]).unwrap());
+    }
+}
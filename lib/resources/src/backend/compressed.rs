@@ -0,0 +1,302 @@
+#[cfg(any(test, feature = "compression_lz4"))]
+extern crate lz4_flex;
+
+use failure;
+use backend::{Backend, BackendMetadata, BackendSyncPoint, Stream};
+#[cfg(any(test, feature = "compression_zlib"))]
+use backend::miniz::{compress_zlib, decompress_zlib};
+use std::io::{self, Read, Seek, SeekFrom};
+use {ResourcePath, ResourcePathBuf, Error};
+
+/// Start of compressed contents, followed by the codec id.
+const MAGIC: &[u8; 4] = b"\x89RCC";
+const HEADER_LEN: usize = 5;
+/// LZ4 blocks expand at most this many times, larger sizes in the prefix are corrupted.
+#[cfg(any(test, feature = "compression_lz4"))]
+const LZ4_MAX_RATIO: usize = 255;
+
+/// Codec used for contents written through the `Compressed` backend.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Codec {
+    /// Contents are written as they are.
+    None,
+    /// Zlib from `miniz_oxide`, level is from 0 to 10.
+    #[cfg(any(test, feature = "compression_zlib"))]
+    Zlib { level: u8 },
+    /// LZ4 block format, faster to decompress than zlib.
+    #[cfg(any(test, feature = "compression_lz4"))]
+    Lz4,
+}
+
+impl Codec {
+    fn id(&self) -> u8 {
+        match *self {
+            Codec::None => 0,
+            #[cfg(any(test, feature = "compression_zlib"))]
+            Codec::Zlib { .. } => 1,
+            #[cfg(any(test, feature = "compression_lz4"))]
+            Codec::Lz4 => 2,
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        match *self {
+            Codec::None => data.to_vec(),
+            #[cfg(any(test, feature = "compression_zlib"))]
+            Codec::Zlib { level } => compress_zlib(data, level),
+            #[cfg(any(test, feature = "compression_lz4"))]
+            Codec::Lz4 => self::lz4_flex::compress_prepend_size(data),
+        }
+    }
+}
+
+#[derive(Fail, Debug)]
+pub enum CodecError {
+    #[fail(display = "Codec {} is not supported", _0)]
+    Unsupported(u8),
+    #[fail(display = "Zlib error {}", _0)]
+    Zlib(String),
+    #[fail(display = "LZ4 error {}", _0)]
+    Lz4(String),
+}
+
+/// Wrapper that compresses written contents and decompresses read contents.
+///
+/// Compressed contents start with a small header that names the codec, so the inner backend
+/// can also contain uncompressed files, which are read as they are. Contents are written
+/// uncompressed if the codec does not make them smaller.
+///
+/// Streams and ranged reads of compressed contents decompress the whole resource into memory.
+#[derive(Debug)]
+pub struct Compressed<T> where T: Backend {
+    inner: T,
+    codec: Codec,
+}
+
+impl<T> Compressed<T> where T: Backend {
+    pub fn new(inner: T, codec: Codec) -> Compressed<T> {
+        Compressed {
+            inner,
+            codec,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn is_compressed(&self, path: &ResourcePath) -> Result<bool, Error> {
+        Ok(self.inner.read_range(path, 0, HEADER_LEN)?.starts_with(MAGIC))
+    }
+}
+
+impl<T> Backend for Compressed<T> where T: Backend {
+    fn can_write(&self) -> bool {
+        self.inner.can_write()
+    }
+
+    fn exists(&self, path: &ResourcePath) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        self.inner.list(dir)
+    }
+
+    fn notify_changes_synced(&mut self, point: BackendSyncPoint) {
+        self.inner.notify_changes_synced(point);
+    }
+
+    fn new_changes(&mut self) -> Option<BackendSyncPoint> {
+        self.inner.new_changes()
+    }
+
+    fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        self.inner.take_modified_paths()
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        let data = decode(path, self.inner.read_vec(path)?)?;
+        output.write_all(&data)?;
+        Ok(())
+    }
+
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        let mut stream = self.inner.open(path)?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        (&mut stream).take(HEADER_LEN as u64).read_to_end(&mut header)?;
+        if !header.starts_with(MAGIC) {
+            stream.seek(SeekFrom::Start(0))?;
+            return Ok(stream);
+        }
+
+        let mut data = header;
+        stream.read_to_end(&mut data)?;
+        Ok(Box::new(io::Cursor::new(decode(path, data)?)))
+    }
+
+    fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
+        let mut data = Vec::new();
+        buffer.read_to_end(&mut data)?;
+        self.inner.write(path, &encode(self.codec, &data))
+    }
+
+    fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        self.inner.delete(path)
+    }
+
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        self.inner.rename(from, to)
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        let mut metadata = self.inner.metadata(path)?;
+        if self.is_compressed(path)? {
+            metadata.len = self.read_vec(path)?.len() as u64;
+        }
+        Ok(metadata)
+    }
+}
+
+fn encode(codec: Codec, data: &[u8]) -> Vec<u8> {
+    let compressed = codec.compress(data);
+    if codec != Codec::None && compressed.len() + HEADER_LEN < data.len() {
+        return [&MAGIC[..], &[codec.id()], &compressed].concat();
+    }
+
+    // uncompressed contents need a header only if they could be mistaken for compressed
+    if data.starts_with(MAGIC) {
+        [&MAGIC[..], &[Codec::None.id()], data].concat()
+    } else {
+        data.to_vec()
+    }
+}
+
+fn decode(path: &ResourcePath, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if data.len() < HEADER_LEN || !data.starts_with(MAGIC) {
+        return Ok(data);
+    }
+
+    let contents = &data[HEADER_LEN..];
+    let result = match data[MAGIC.len()] {
+        0 => Ok(contents.to_vec()),
+        #[cfg(any(test, feature = "compression_zlib"))]
+        1 => decompress_zlib(contents)
+            .map_err(|e| CodecError::Zlib(format!("{:?}", e))),
+        #[cfg(any(test, feature = "compression_lz4"))]
+        2 => decompress_lz4(contents),
+        id => Err(CodecError::Unsupported(id)),
+    };
+
+    result.map_err(|e| Error::FailedToDecode {
+        path: path.into(),
+        inner: failure::Error::from(e),
+    })
+}
+
+/// Decompresses the LZ4 block after its uncompressed size, checking the size before allocating.
+#[cfg(any(test, feature = "compression_lz4"))]
+fn decompress_lz4(contents: &[u8]) -> Result<Vec<u8>, CodecError> {
+    if contents.len() < 4 {
+        return Err(CodecError::Lz4("size prefix is missing".into()));
+    }
+    let (prefix, block) = contents.split_at(4);
+    let len = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+    if len > block.len().saturating_mul(LZ4_MAX_RATIO) {
+        return Err(CodecError::Lz4(format!("size {} is too large for {} compressed bytes", len, block.len())));
+    }
+
+    let mut data = vec![0; len];
+    let written = self::lz4_flex::decompress_into(block, &mut data)
+        .map_err(|e| CodecError::Lz4(e.to_string()))?;
+    if written != len {
+        return Err(CodecError::Lz4(format!("decompressed {} bytes instead of {}", written, len)));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use backend::{Backend, Compressed, Codec, InMemory};
+    use Error;
+
+    fn round_trip(codec: Codec) {
+        let long = [b'a'; 1000];
+        let mut be = Compressed::new(InMemory::new(), codec);
+
+        be.write("x".into(), &long).unwrap();
+
+        assert!(be.inner().read_vec("x".into()).unwrap().len() < 1000);
+        assert_eq!(&long[..], &be.read_vec("x".into()).unwrap()[..]);
+        assert_eq!(b"aaa", &be.read_range("x".into(), 997, 100).unwrap()[..]);
+        assert_eq!(1000, be.metadata("x".into()).unwrap().len);
+    }
+
+    #[test]
+    fn test_zlib_round_trip() {
+        round_trip(Codec::Zlib { level: 9 });
+    }
+
+    #[test]
+    fn test_lz4_round_trip() {
+        round_trip(Codec::Lz4);
+    }
+
+    #[test]
+    fn test_uncompressed_files_are_passed_through() {
+        let mut be = Compressed::new(InMemory::new().with("plain", b"hello world"), Codec::Zlib { level: 9 });
+
+        assert_eq!(b"hello world", &be.read_vec("plain".into()).unwrap()[..]);
+        assert_eq!(b"world", &be.read_range("plain".into(), 6, 100).unwrap()[..]);
+        assert_eq!(11, be.metadata("plain".into()).unwrap().len);
+
+        // too short to shrink
+        be.write("short".into(), b"abc").unwrap();
+        assert_eq!(b"abc", &be.inner().read_vec("short".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_uncompressed_contents_that_start_with_magic_are_stored_with_header() {
+        let mut be = Compressed::new(InMemory::new(), Codec::None);
+
+        be.write("x".into(), b"\x89RCC\x01not zlib").unwrap();
+
+        assert_eq!(b"\x89RCC\x00\x89RCC\x01not zlib", &be.inner().read_vec("x".into()).unwrap()[..]);
+        assert_eq!(b"\x89RCC\x01not zlib", &be.read_vec("x".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_decode_errors_name_the_resource() {
+        let be = Compressed::new(
+            InMemory::new()
+                .with("shaders/bad.vert", b"\x89RCC\x01not zlib")
+                .with("unknown", b"\x89RCC\x7fdata"),
+            Codec::None,
+        );
+
+        for path in &["shaders/bad.vert", "unknown"] {
+            match be.read_vec((*path).into()) {
+                Err(Error::FailedToDecode { path: ref failed, .. }) => assert_eq!(*path, failed.as_clean_str()),
+                other => panic!("expected FailedToDecode, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lz4_sizes_larger_than_possible_are_rejected() {
+        let be = Compressed::new(
+            InMemory::new()
+                .with("huge", b"\x89RCC\x02\xff\xff\xff\xff\x10a")
+                .with("short", b"\x89RCC\x02\x02\x00\x00\x00\x10a"),
+            Codec::Lz4,
+        );
+
+        for path in &["huge", "short"] {
+            match be.read_vec((*path).into()) {
+                Err(Error::FailedToDecode { .. }) => (),
+                other => panic!("expected FailedToDecode, got {:?}", other),
+            }
+        }
+    }
+}
@@ -1,13 +1,37 @@
-use std::sync::{Arc, RwLock};
-use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak, RwLock};
+use std::path::{Component, Path, PathBuf};
 use std::io;
-use backend::{Backend, BackendSyncPoint};
-use {ResourcePath, Error};
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use twox_hash::XxHash;
+use backend::{Backend, BackendMetadata, BackendSyncPoint, Stream};
+use {ResourcePath, ResourcePathBuf, Error};
+
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 500;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct FileState {
+    modified: SystemTime,
+    len: u64,
+}
+
+type FileStates = HashMap<ResourcePathBuf, FileState, BuildHasherDefault<XxHash>>;
+
+/// Last known state of files under the root, and paths that changed since it was last taken.
+struct Watch {
+    files: FileStates,
+    modified_paths: Vec<ResourcePathBuf>,
+}
 
 struct Shared {
     root_path: PathBuf,
     can_write: bool,
-    watch: bool,
+    watch: Option<Watch>,
+    write_generation: usize,
+    unsynced_change_time: Option<BackendSyncPoint>,
 }
 
 impl Shared {
@@ -15,7 +39,9 @@ impl Shared {
         Shared {
             root_path,
             can_write: false,
-            watch: false,
+            watch: None,
+            write_generation: 0,
+            unsynced_change_time: None,
         }
     }
 
@@ -23,12 +49,168 @@ impl Shared {
         self.can_write = flag;
     }
 
-    pub fn set_watch(&mut self, flag: bool) {
-        self.watch = flag;
+    pub fn start_watch(&mut self) {
+        self.watch = Some(Watch {
+            files: scan_files(&self.root_path),
+            modified_paths: Vec::new(),
+        });
+    }
+
+    /// Compares a fresh scan with the last known file state and records the differences.
+    ///
+    /// The scan is discarded if this backend wrote a file while it was running.
+    pub fn apply_scan(&mut self, files: FileStates, write_generation: usize) {
+        if self.write_generation != write_generation {
+            return;
+        }
+
+        let watch = match self.watch {
+            Some(ref mut watch) => watch,
+            None => return,
+        };
+
+        let previous_len = watch.modified_paths.len();
+
+        for (path, state) in files.iter() {
+            if watch.files.get(path) != Some(state) {
+                watch.modified_paths.push(path.clone());
+            }
+        }
+        for path in watch.files.keys() {
+            if !files.contains_key(path) {
+                watch.modified_paths.push(path.clone());
+            }
+        }
+
+        watch.files = files;
+
+        if watch.modified_paths.len() > previous_len {
+            self.unsynced_change_time = Some(BackendSyncPoint::now());
+        }
+    }
+
+    pub fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        match self.watch {
+            Some(ref mut watch) => ::std::mem::replace(&mut watch.modified_paths, Vec::new()),
+            None => Vec::new(),
+        }
     }
 
     pub fn resource_exists(&self, path: &ResourcePath) -> bool {
-        resource_name_to_path(&self.root_path, path).exists()
+        resource_name_to_path(&self.root_path, path)
+            .map(|path| path.is_file())
+            .unwrap_or(false)
+    }
+
+    pub fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        let (fs_dir, dir) = match (resource_name_to_path(&self.root_path, dir), dir.normalize()) {
+            (Ok(fs_dir), Some(dir)) => (fs_dir, dir),
+            _ => return Vec::new(),
+        };
+
+        let mut paths = Vec::new();
+        visit_files(&fs_dir, dir.as_clean_str(), &mut |path, _| {
+            paths.push(path.into());
+        });
+        paths
+    }
+
+    pub fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        let mut file = self.open(path)?;
+        io::copy(&mut file, output)?;
+        Ok(())
+    }
+
+    pub fn open(&self, path: &ResourcePath) -> Result<fs::File, Error> {
+        fs::File::open(resource_name_to_path(&self.root_path, path)?)
+            .map_err(not_found_or_io)
+    }
+
+    pub fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
+        if !self.can_write {
+            return Err(Error::NotWritable);
+        }
+
+        let fs_path = resource_name_to_path(&self.root_path, path)?;
+        if let Some(parent) = fs_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = fs::File::create(&fs_path)?;
+        io::copy(buffer, &mut file)?;
+
+        self.record_own_change(path);
+
+        Ok(())
+    }
+
+    pub fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        if !self.can_write {
+            return Err(Error::NotWritable);
+        }
+
+        fs::remove_file(resource_name_to_path(&self.root_path, path)?)
+            .map_err(not_found_or_io)?;
+
+        self.record_own_change(path);
+
+        Ok(())
+    }
+
+    pub fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        if !self.can_write {
+            return Err(Error::NotWritable);
+        }
+
+        let from_path = resource_name_to_path(&self.root_path, from)?;
+        if !from_path.is_file() {
+            return Err(Error::NotFound);
+        }
+        let to_path = resource_name_to_path(&self.root_path, to)?;
+        if let Some(parent) = to_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(from_path, to_path)?;
+
+        self.record_own_change(from);
+        self.record_own_change(to);
+
+        Ok(())
+    }
+
+    pub fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        let metadata = fs::metadata(resource_name_to_path(&self.root_path, path)?)
+            .map_err(not_found_or_io)?;
+        if !metadata.is_file() {
+            return Err(Error::NotFound);
+        }
+
+        Ok(BackendMetadata {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// Marks the change made by this backend as known to resources.
+    ///
+    /// The file state is updated in the watch snapshot, so the watcher does not report it again.
+    fn record_own_change(&mut self, path: &ResourcePath) {
+        self.write_generation = self.write_generation.wrapping_add(1);
+        if let Some(ref mut watch) = self.watch {
+            if let (Some(path), Ok(fs_path)) = (path.normalize(), resource_name_to_path(&self.root_path, path)) {
+                match file_state(&fs_path) {
+                    Some(state) => {
+                        watch.files.insert(path, state);
+                    },
+                    None => {
+                        watch.files.remove(&path);
+                    },
+                }
+            }
+        }
+
+        self.unsynced_change_time = Some(BackendSyncPoint::now());
     }
 }
 
@@ -37,8 +219,14 @@ pub struct FileSystem {
 }
 
 impl FileSystem {
+    /// Creates the backend for the directory relative to the root path, the relative path
+    /// can lead outside of the root path.
     pub fn from_rel_path<P: AsRef<Path>, RP: AsRef<ResourcePath>>(root_path: P, rel_path: RP) -> FileSystem {
-        FileSystem::from_path(resource_name_to_path(root_path.as_ref(), rel_path.as_ref()))
+        let mut path: PathBuf = root_path.as_ref().into();
+        for part in rel_path.as_ref().items() {
+            path.push(part);
+        }
+        FileSystem::from_path(path)
     }
 
     pub fn from_path<P: AsRef<Path>>(root_path: P) -> FileSystem {
@@ -53,9 +241,16 @@ impl FileSystem {
         self
     }
 
+    /// Watch the root directory for files modified, created or deleted by other processes.
     pub fn with_watch(self) -> Self {
+        self.with_watch_interval(Duration::from_millis(DEFAULT_WATCH_INTERVAL_MS))
+    }
+
+    /// Watch the root directory, polling file modification times at the specified interval.
+    pub fn with_watch_interval(self, interval: Duration) -> Self {
         self.shared.write().expect("failed to lock FileSystem for write")
-            .set_watch(true);
+            .start_watch();
+        spawn_watcher(Arc::downgrade(&self.shared), interval);
         self
     }
 }
@@ -71,29 +266,459 @@ impl Backend for FileSystem {
             .resource_exists(path)
     }
 
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        self.shared.read().expect("failed to lock FileSystem for read")
+            .list(dir)
+    }
+
     fn notify_changes_synced(&mut self, point: BackendSyncPoint) {
-        unimplemented!()
+        let mut shared = self.shared.write().expect("failed to lock FileSystem for write");
+
+        if shared.unsynced_change_time == Some(point) {
+            shared.unsynced_change_time = None;
+        }
     }
 
     fn new_changes(&mut self) -> Option<BackendSyncPoint> {
-        unimplemented!()
+        self.shared.read().expect("failed to lock FileSystem for read")
+            .unsynced_change_time
+    }
+
+    fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        self.shared.write().expect("failed to lock FileSystem for write")
+            .take_modified_paths()
     }
 
-    fn read_into(&mut self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
-        unimplemented!()
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        self.shared.read().expect("failed to lock FileSystem for read")
+            .read_into(path, output)
+    }
+
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        let file = self.shared.read().expect("failed to lock FileSystem for read")
+            .open(path)?;
+        Ok(Box::new(file))
     }
 
     fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
-        unimplemented!()
+        self.shared.write().expect("failed to lock FileSystem for write")
+            .write_from(path, buffer)
+    }
+
+    fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        self.shared.write().expect("failed to lock FileSystem for write")
+            .delete(path)
+    }
+
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        self.shared.write().expect("failed to lock FileSystem for write")
+            .rename(from, to)
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        self.shared.read().expect("failed to lock FileSystem for read")
+            .metadata(path)
     }
 }
 
-fn resource_name_to_path(root_dir: &Path, location: &ResourcePath) -> PathBuf {
+/// Returns the path of the resource under the root directory.
+///
+/// Fails if the resource path leads outside of the root directory.
+fn resource_name_to_path(root_dir: &Path, location: &ResourcePath) -> Result<PathBuf, Error> {
+    let outside_root = || Error::PathOutsideRoot { path: location.into() };
+    let location = location.normalize().ok_or_else(outside_root)?;
     let mut path: PathBuf = root_dir.into();
 
-    for part in location.items() {
-        path = path.join(part);
+    for part in location.items().filter(|part| !part.is_empty()) {
+        // on Windows, items like "C:" would replace the root
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => path.push(part),
+            _ => return Err(outside_root()),
+        }
     }
 
-    path
+    Ok(path)
+}
+
+fn spawn_watcher(shared: Weak<RwLock<Shared>>, interval: Duration) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(interval);
+
+            // the thread stops when the FileSystem backend is dropped
+            let shared = match shared.upgrade() {
+                Some(shared) => shared,
+                None => return,
+            };
+
+            // the tree is scanned without the lock, writes made during the scan change
+            // the generation and the scan is discarded
+            let (root_path, write_generation) = {
+                let shared = shared.read().expect("failed to lock FileSystem for read");
+                (shared.root_path.clone(), shared.write_generation)
+            };
+            let files = scan_files(&root_path);
+
+            shared.write().expect("failed to lock FileSystem for write")
+                .apply_scan(files, write_generation);
+        }
+    });
+}
+
+fn scan_files(root_dir: &Path) -> FileStates {
+    let mut files = FileStates::default();
+    visit_files(root_dir, "", &mut |path, fs_path| {
+        if let Some(state) = file_state(fs_path) {
+            files.insert(path.into(), state);
+        }
+    });
+    files
+}
+
+/// Calls the visitor with resource path and file system path of every file in the directory tree.
+fn visit_files(dir: &Path, resource_dir: &str, visitor: &mut FnMut(String, &Path)) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        let resource_path = if resource_dir.is_empty() {
+            name
+        } else {
+            [resource_dir, "/", &name].concat()
+        };
+
+        let fs_path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        if file_type.is_dir() {
+            visit_files(&fs_path, &resource_path, visitor);
+        } else if !fs_path.is_dir() {
+            // symlinks to directories are not followed, they could lead into a loop
+            visitor(resource_path, &fs_path);
+        }
+    }
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(FileState {
+        modified: metadata.modified().ok()?,
+        len: metadata.len(),
+    })
+}
+
+fn not_found_or_io(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::NotFound => Error::NotFound,
+        _ => Error::Io(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tempdir;
+
+    use self::tempdir::TempDir;
+    use backend::{Backend, FileSystem};
+    use {ChangeKind, Error, Resources, ResourcePathBuf};
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn watched(path: &::std::path::Path) -> FileSystem {
+        FileSystem::from_path(path).with_write().with_watch_interval(Duration::from_millis(5))
+    }
+
+    fn wait_for<F: FnMut() -> bool>(mut condition: F) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_can_read_existing_file() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("x.txt"), b"hello world").unwrap();
+
+        let be = FileSystem::from_path(dir.path());
+
+        assert!(be.exists("x.txt".into()));
+        assert_eq!(b"hello world", &be.read_vec("x.txt".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_paths_are_normalized_and_can_not_lead_outside_of_root() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::create_dir(dir.path().join("root")).unwrap();
+        fs::write(dir.path().join("secret.txt"), b"secret").unwrap();
+        fs::write(dir.path().join("root").join("x.txt"), b"hello").unwrap();
+
+        let mut be = FileSystem::from_path(dir.path().join("root")).with_write();
+
+        assert_eq!(b"hello", &be.read_vec("a/../x.txt".into()).unwrap()[..]);
+        assert!(!be.exists("../secret.txt".into()));
+        match be.read_vec("../secret.txt".into()) {
+            Err(Error::PathOutsideRoot { .. }) => (),
+            other => panic!("expected PathOutsideRoot, got {:?}", other),
+        }
+        match be.write_from("a/../../secret.txt".into(), &mut &b"changed"[..]) {
+            Err(Error::PathOutsideRoot { .. }) => (),
+            other => panic!("expected PathOutsideRoot, got {:?}", other),
+        }
+        assert_eq!(b"secret", &fs::read(dir.path().join("secret.txt")).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_can_read_range_of_existing_file() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("x.txt"), b"hello world").unwrap();
+        let be = FileSystem::from_path(dir.path());
+
+        assert_eq!(b"world", &be.read_range("x.txt".into(), 6, 100).unwrap()[..]);
+        assert_eq!(b"", &be.read_range("x.txt".into(), 100, 1).unwrap()[..]);
+        assert_eq!(Err(Error::NotFound), be.read_range("y.txt".into(), 0, 1));
+    }
+
+    #[test]
+    fn test_delete_rename_and_metadata_are_not_reported_by_watcher() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("x.txt"), b"hello").unwrap();
+        fs::write(dir.path().join("y.txt"), b"world").unwrap();
+        let mut be = watched(dir.path());
+
+        let metadata = be.metadata("x.txt".into()).unwrap();
+        assert_eq!(5, metadata.len);
+        assert!(metadata.modified.is_some());
+        assert_eq!(Err(Error::NotFound), be.metadata("sub".into()));
+
+        be.rename("x.txt".into(), "sub/z.txt".into()).unwrap();
+        be.delete("y.txt".into()).unwrap();
+        assert_eq!(Err(Error::NotFound), be.delete("y.txt".into()));
+
+        assert_eq!(b"hello", &fs::read(dir.path().join("sub/z.txt")).unwrap()[..]);
+        assert!(!dir.path().join("x.txt").exists());
+        assert!(!dir.path().join("y.txt").exists());
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(be.take_modified_paths().is_empty());
+    }
+
+    #[test]
+    fn test_missing_file_is_not_found() {
+        let dir = TempDir::new("resources").unwrap();
+        let be = FileSystem::from_path(dir.path());
+
+        assert!(!be.exists("missing.txt".into()));
+        assert_eq!(Err(Error::NotFound), be.read_vec("missing.txt".into()));
+    }
+
+    #[test]
+    fn test_can_not_write_without_write_flag() {
+        let dir = TempDir::new("resources").unwrap();
+        let mut be = FileSystem::from_path(dir.path());
+
+        assert!(!be.can_write());
+        match be.write("x.txt".into(), b"hello") {
+            Err(Error::NotWritable) => (),
+            other => panic!("expected NotWritable, got {:?}", other),
+        }
+        assert!(!dir.path().join("x.txt").exists());
+    }
+
+    #[test]
+    fn test_write_creates_parent_dirs_and_can_be_read_back() {
+        let dir = TempDir::new("resources").unwrap();
+        let mut be = FileSystem::from_path(dir.path()).with_write();
+
+        be.write("shaders/nested/x.vert".into(), b"hello world").unwrap();
+
+        assert_eq!(b"hello world", &fs::read(dir.path().join("shaders").join("nested").join("x.vert")).unwrap()[..]);
+        assert_eq!(b"hello world", &be.read_vec("shaders/nested/x.vert".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_write_produces_sync_point_until_synced() {
+        let dir = TempDir::new("resources").unwrap();
+        let mut be = FileSystem::from_path(dir.path()).with_write();
+
+        assert!(be.new_changes().is_none());
+
+        be.write("x.txt".into(), b"hello").unwrap();
+
+        let point = be.new_changes().unwrap();
+        be.notify_changes_synced(point);
+
+        assert!(be.new_changes().is_none());
+    }
+
+    #[test]
+    fn test_watch_reports_modified_created_and_deleted_files() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("modified.txt"), b"hello").unwrap();
+        fs::write(dir.path().join("deleted.txt"), b"hello").unwrap();
+
+        let mut be = watched(dir.path());
+
+        fs::write(dir.path().join("modified.txt"), b"hello world").unwrap();
+        fs::remove_file(dir.path().join("deleted.txt")).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("created.txt"), b"hello").unwrap();
+
+        let mut paths = Vec::new();
+        assert!(wait_for(|| {
+            paths.extend(be.take_modified_paths());
+            paths.len() >= 3
+        }));
+        assert!(be.new_changes().is_some());
+
+        paths.sort();
+        assert_eq!(
+            vec![
+                ResourcePathBuf::from(String::from("deleted.txt")),
+                ResourcePathBuf::from(String::from("modified.txt")),
+                ResourcePathBuf::from(String::from("sub/created.txt")),
+            ],
+            paths
+        );
+    }
+
+    #[test]
+    fn test_watch_does_not_report_own_writes() {
+        let dir = TempDir::new("resources").unwrap();
+        let mut be = watched(dir.path());
+
+        be.write("x.txt".into(), b"hello").unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(be.take_modified_paths().is_empty());
+    }
+
+    #[test]
+    fn test_external_modification_marks_resource_as_modified() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("x.txt"), b"hello").unwrap();
+
+        let res = Resources::new()
+            .loaded_from("a", 0, watched(dir.path()));
+        let resource = res.resource("x.txt");
+
+        assert!(res.new_changes().is_none());
+
+        fs::write(dir.path().join("x.txt"), b"hello world").unwrap();
+
+        let point = {
+            let mut point = None;
+            assert!(wait_for(|| { point = res.new_changes(); point.is_some() }));
+            point.unwrap()
+        };
+
+        assert!(resource.is_modified());
+        assert_eq!(b"hello world", &resource.get().unwrap()[..]);
+        assert!(!resource.is_modified());
+
+        res.notify_changes_synced(point);
+        assert!(res.new_changes().is_none());
+    }
+
+    #[test]
+    fn test_external_changes_are_sent_to_subscribers() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::write(dir.path().join("x.txt"), b"hello").unwrap();
+        fs::write(dir.path().join("y.txt"), b"hello").unwrap();
+
+        let res = Resources::new()
+            .loaded_from("a", 0, watched(dir.path()));
+        let changes = res.subscribe();
+
+        fs::write(dir.path().join("x.txt"), b"hello world").unwrap();
+        fs::remove_file(dir.path().join("y.txt")).unwrap();
+
+        let mut received = Vec::new();
+        assert!(wait_for(|| {
+            res.new_changes();
+            received.extend(changes.try_iter());
+            received.len() >= 2
+        }));
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            received,
+            vec![
+                (ResourcePathBuf::from(String::from("x.txt")), ChangeKind::Modified),
+                (ResourcePathBuf::from(String::from("y.txt")), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_returns_files_in_subdirectories() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::create_dir_all(dir.path().join("objs").join("more")).unwrap();
+        fs::write(dir.path().join("objs").join("dice.obj"), b"").unwrap();
+        fs::write(dir.path().join("objs").join("more").join("cube.obj"), b"").unwrap();
+        fs::write(dir.path().join("Config.toml"), b"").unwrap();
+
+        let be = FileSystem::from_path(dir.path());
+
+        let mut paths = be.list("objs".into());
+        paths.sort();
+        assert_eq!(
+            vec![
+                ResourcePathBuf::from(String::from("objs/dice.obj")),
+                ResourcePathBuf::from(String::from("objs/more/cube.obj")),
+            ],
+            paths
+        );
+        assert_eq!(3, be.list("".into()).len());
+        assert!(be.list("missing".into()).is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlinked_directories_are_not_followed() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new("resources").unwrap();
+        fs::create_dir(dir.path().join("objs")).unwrap();
+        fs::write(dir.path().join("objs").join("dice.obj"), b"").unwrap();
+        symlink(dir.path(), dir.path().join("objs").join("loop")).unwrap();
+        symlink(dir.path().join("objs").join("dice.obj"), dir.path().join("objs").join("linked.obj")).unwrap();
+
+        let be = FileSystem::from_path(dir.path());
+
+        let mut paths = be.list("".into());
+        paths.sort();
+        assert_eq!(
+            vec![
+                ResourcePathBuf::from(String::from("objs/dice.obj")),
+                ResourcePathBuf::from(String::from("objs/linked.obj")),
+            ],
+            paths
+        );
+    }
+
+    #[test]
+    fn test_from_rel_path_reads_from_subdirectory() {
+        let dir = TempDir::new("resources").unwrap();
+        fs::create_dir(dir.path().join("core")).unwrap();
+        fs::write(dir.path().join("core").join("Config.toml"), b"a = 1").unwrap();
+
+        let be = FileSystem::from_rel_path(dir.path(), "core");
+
+        assert_eq!(b"a = 1", &be.read_vec("Config.toml".into()).unwrap()[..]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+use std::hash::Hasher;
+use std::io;
+use twox_hash::XxHash;
+
+/// Hash of resource contents, stored in pack indexes and verification manifests.
+pub fn content_hash(data: &[u8]) -> u64 {
+    let mut hasher = ContentHasher::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// Computes `content_hash` of contents written in parts.
+pub struct ContentHasher {
+    hasher: XxHash,
+    len: u64,
+}
+
+impl ContentHasher {
+    pub fn new() -> ContentHasher {
+        ContentHasher {
+            hasher: XxHash::with_seed(0),
+            len: 0,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.write(data);
+        self.len += data.len() as u64;
+    }
+
+    /// Returns the number of bytes hashed so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+}
+
+impl io::Write for ContentHasher {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{content_hash, ContentHasher};
+    use std::io::Write;
+
+    #[test]
+    fn hash_of_parts_should_match_hash_of_whole_contents() {
+        let mut hasher = ContentHasher::new();
+        hasher.write_all(b"hello ").unwrap();
+        hasher.write_all(b"world").unwrap();
+
+        assert_eq!(content_hash(b"hello world"), hasher.finish());
+        assert_eq!(11, hasher.len());
+        assert_ne!(content_hash(b"hello world"), content_hash(b"hello World"));
+    }
+}
@@ -1,4 +1,4 @@
-use backend::{Backend, BackendSyncPoint};
+use backend::{Backend, BackendMetadata, BackendSyncPoint, Stream};
 use std::sync::{RwLock, Arc};
 use std::collections::HashMap;
 use std::io;
@@ -8,7 +8,7 @@ use {ResourcePathBuf, ResourcePath, Error};
 
 #[derive(Debug)]
 struct Shared {
-    map: HashMap<ResourcePathBuf, Vec<u8>, BuildHasherDefault<XxHash>>,
+    map: HashMap<ResourcePathBuf, Arc<[u8]>, BuildHasherDefault<XxHash>>,
     unsynced_change_time: Option<BackendSyncPoint>,
 }
 
@@ -25,6 +25,15 @@ impl Shared {
     }
 }
 
+/// Contents shared by the map and open streams.
+struct Contents(Arc<[u8]>);
+
+impl AsRef<[u8]> for Contents {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 #[derive(Debug)]
 pub struct InMemory {
     shared: Arc<RwLock<Shared>>,
@@ -43,6 +52,12 @@ impl InMemory {
             .insert(key.as_ref(), value);
         self
     }
+
+    /// Creates the backend from files included by `embedded_resources!`.
+    pub fn from_embedded(files: &[(&str, &[u8])]) -> Self {
+        files.iter()
+            .fold(InMemory::new(), |backend, &(path, contents)| backend.with(path, contents))
+    }
 }
 
 impl Backend for InMemory {
@@ -55,6 +70,18 @@ impl Backend for InMemory {
             .map.contains_key::<ResourcePath>( path.as_clean_str().as_ref())
     }
 
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        let dir = dir.as_clean_str();
+        self.shared.read().expect("failed to lock InMemory for read")
+            .map.keys()
+            .filter(|path| dir.is_empty() || {
+                let path = path.as_clean_str();
+                path.starts_with(dir) && path[dir.len()..].starts_with('/')
+            })
+            .cloned()
+            .collect()
+    }
+
     fn notify_changes_synced(&mut self, point: BackendSyncPoint) {
         let mut shared_ref = self.shared.write().expect("failed to lock InMemory for write");
 
@@ -68,7 +95,7 @@ impl Backend for InMemory {
             .unsynced_change_time
     }
 
-    fn read_into(&mut self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
         let shared = self.shared.read().expect("failed to lock InMemory for read");
         let item_ref = match shared.map.get(path) {
             None => return Err(Error::NotFound),
@@ -78,14 +105,50 @@ impl Backend for InMemory {
         Ok(())
     }
 
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        let shared = self.shared.read().expect("failed to lock InMemory for read");
+        match shared.map.get(path) {
+            None => Err(Error::NotFound),
+            Some(val) => Ok(Box::new(io::Cursor::new(Contents(val.clone())))),
+        }
+    }
+
     fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
         let mut data = Vec::new();
         buffer.read_to_end(&mut data)?;
 
         let mut shared = self.shared.write().expect("failed to lock InMemory for write");
-        shared.map.insert(path.into(), data);
+        shared.map.insert(path.into(), data.into());
         shared.unsynced_change_time = Some(BackendSyncPoint::now());
 
         Ok(())
     }
+
+    fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        let mut shared = self.shared.write().expect("failed to lock InMemory for write");
+        shared.map.remove(path).ok_or(Error::NotFound)?;
+        shared.unsynced_change_time = Some(BackendSyncPoint::now());
+
+        Ok(())
+    }
+
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        let mut shared = self.shared.write().expect("failed to lock InMemory for write");
+        let data = shared.map.remove(from).ok_or(Error::NotFound)?;
+        shared.map.insert(to.into(), data);
+        shared.unsynced_change_time = Some(BackendSyncPoint::now());
+
+        Ok(())
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        let shared = self.shared.read().expect("failed to lock InMemory for read");
+        match shared.map.get(path) {
+            None => Err(Error::NotFound),
+            Some(val) => Ok(BackendMetadata {
+                len: val.len() as u64,
+                modified: None,
+            }),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+extern crate miniz_oxide as miniz;
+
+pub(crate) use self::miniz::inflate::TINFLStatus;
+use self::miniz::inflate::core::{decompress, inflate_flags, DecompressorOxide};
+use std::cmp;
+use std::io::Cursor;
+
+/// Inflates deflate data, or zlib data if `zlib` is set.
+///
+/// The output grows while the data is inflated, up to `limit`. The decompression fails
+/// with `HasMoreOutput` if the data is longer.
+pub(crate) fn inflate_with_limit(data: &[u8], limit: usize, zlib: bool) -> Result<Vec<u8>, TINFLStatus> {
+    let mut flags = inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF;
+    if zlib {
+        flags |= inflate_flags::TINFL_FLAG_PARSE_ZLIB_HEADER;
+    }
+
+    // the decompressor does not return when the output buffer is empty, so it has at least a byte
+    let mut output = vec![0; cmp::max(1, cmp::min(limit, cmp::max(64, data.len().saturating_mul(2))))];
+    let mut decompressor = Box::<DecompressorOxide>::default();
+    let mut in_pos = 0;
+    let mut out_pos = 0;
+
+    loop {
+        let (status, in_consumed, out_consumed) = {
+            let mut cursor = Cursor::new(output.as_mut_slice());
+            cursor.set_position(out_pos as u64);
+            decompress(&mut decompressor, &data[in_pos..], &mut cursor, flags)
+        };
+        in_pos += in_consumed;
+        out_pos += out_consumed;
+
+        match status {
+            TINFLStatus::Done if out_pos > limit => return Err(TINFLStatus::HasMoreOutput),
+            TINFLStatus::Done => {
+                output.truncate(out_pos);
+                return Ok(output);
+            },
+            TINFLStatus::HasMoreOutput if output.len() < limit => {
+                let len = cmp::min(limit, output.len().saturating_mul(2));
+                output.resize(len, 0);
+            },
+            _ => return Err(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{inflate_with_limit, miniz};
+    use super::miniz::inflate::TINFLStatus;
+
+    #[test]
+    fn test_inflates_up_to_the_limit() {
+        let data = vec![7u8; 10000];
+        let deflated = miniz::deflate::compress_to_vec(&data, 6);
+        let zlib = miniz::deflate::compress_to_vec_zlib(&data, 6);
+
+        assert_eq!(Ok(data.clone()), inflate_with_limit(&deflated, 10000, false));
+        assert_eq!(Ok(data.clone()), inflate_with_limit(&zlib, 10000, true));
+        // empty final block, the compressor does not return for empty data
+        assert_eq!(Ok(Vec::new()), inflate_with_limit(&[0x03, 0x00], 0, false));
+    }
+
+    #[test]
+    fn test_output_longer_than_the_limit_fails() {
+        let deflated = miniz::deflate::compress_to_vec(&vec![0u8; 1 << 20], 6);
+
+        assert_eq!(Err(TINFLStatus::HasMoreOutput), inflate_with_limit(&deflated, 1000, false));
+        assert_eq!(Err(TINFLStatus::HasMoreOutput), inflate_with_limit(&deflated, (1 << 20) - 1, false));
+        assert_eq!(Err(TINFLStatus::HasMoreOutput), inflate_with_limit(&miniz::deflate::compress_to_vec(&[1; 100], 6), 0, false));
+    }
+}
@@ -5,6 +5,11 @@ use backend::{Backend, BackendSyncPoint};
 use std::io::{self};
 use {ResourcePath, ResourcePathBuf, Error};
 
+/// Wrapper that compresses written contents with zlib and decompresses read contents.
+///
+/// Despite the name, the codec is zlib, and all contents of the inner backend must be compressed.
+/// Prefer `Compressed` with `Codec::Zlib`, which also reads uncompressed files.
+/// Streams and ranged reads decompress the whole resource into memory.
 #[derive(Debug)]
 pub struct Lzma<T> where T: Backend {
     inner: T,
@@ -29,6 +34,10 @@ impl<T> Backend for Lzma<T> where T: Backend {
         self.inner.exists(path)
     }
 
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        self.inner.list(dir)
+    }
+
     fn notify_changes_synced(&mut self, point: BackendSyncPoint) {
         self.inner.notify_changes_synced(point);
     }
@@ -37,10 +46,14 @@ impl<T> Backend for Lzma<T> where T: Backend {
         self.inner.new_changes()
     }
 
-    fn read_into(&mut self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+    fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        self.inner.take_modified_paths()
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
         let mut input_data = Vec::new();
         self.inner.read_into(path, &mut input_data)?;
-        let output_data = self::miniz::inflate::decompress_to_vec_zlib(&mut input_data).map_err(write_error)?;
+        let output_data = decompress_zlib(&input_data).map_err(|e| decode_error(path, e))?;
         output.write_all(&output_data[..])?;
         Ok(())
     }
@@ -48,10 +61,18 @@ impl<T> Backend for Lzma<T> where T: Backend {
     fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
         let mut input_data = Vec::new();
         buffer.read_to_end(&mut input_data)?;
-        let output_data = self::miniz::deflate::compress_to_vec_zlib(&mut input_data, self.level);
+        let output_data = compress_zlib(&input_data, self.level);
         let mut cursor = io::Cursor::new(output_data);
         Ok(self.inner.write_from(path, &mut cursor)?)
     }
+
+    fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        self.inner.delete(path)
+    }
+
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        self.inner.rename(from, to)
+    }
 }
 
 #[derive(Fail, Debug)]
@@ -60,9 +81,17 @@ pub enum MinizError {
     ErrorCode(self::miniz::inflate::TINFLStatus),
 }
 
-fn write_error(miniz_error: self::miniz::inflate::TINFLStatus) -> Error {
-    Error::BackendFailedToWrite {
-        path: ResourcePathBuf::from(String::from("")),
+pub(crate) fn compress_zlib(data: &[u8], level: u8) -> Vec<u8> {
+    self::miniz::deflate::compress_to_vec_zlib(data, level)
+}
+
+pub(crate) fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, self::miniz::inflate::TINFLStatus> {
+    self::miniz::inflate::decompress_to_vec_zlib(data)
+}
+
+fn decode_error(path: &ResourcePath, miniz_error: self::miniz::inflate::TINFLStatus) -> Error {
+    Error::FailedToDecode {
+        path: path.into(),
         inner: failure::Error::from(MinizError::ErrorCode(miniz_error)),
     }
 }
@@ -80,4 +109,13 @@ mod test {
 
         assert_eq!(b"hello world", &result[..]);
     }
+
+    #[test]
+    fn test_ranged_read_falls_back_to_decompressed_contents() {
+        let mut be = Lzma::new(InMemory::new(), 9);
+
+        be.write("x".into(), b"hello world").unwrap();
+
+        assert_eq!(b"world", &be.read_range("x".into(), 6, 100).unwrap()[..]);
+    }
 }
\ No newline at end of file
@@ -1,7 +1,11 @@
-use path::{ResourcePath};
+use path::{ResourcePath, ResourcePathBuf};
 use Error;
 use std::io;
-use std::time::Instant;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::{Instant, SystemTime};
+
+mod hash;
+pub use self::hash::{content_hash, ContentHasher};
 
 #[cfg(any(test, feature = "backend_in_memory"))]
 mod in_memory;
@@ -10,14 +14,58 @@ pub use self::in_memory::InMemory;
 
 #[cfg(any(test, feature = "backend_miniz"))]
 mod miniz;
+
+#[cfg(any(test, feature = "backend_zip", feature = "backend_pack"))]
+mod inflate;
 #[cfg(any(test, feature = "backend_miniz"))]
 pub use self::miniz::Lzma;
 
+#[cfg(any(test, feature = "backend_compressed"))]
+mod compressed;
+#[cfg(any(test, feature = "backend_compressed"))]
+pub use self::compressed::{Compressed, Codec, CodecError};
+
 #[cfg(any(test, feature = "backend_filesystem"))]
 mod filesystem;
 #[cfg(any(test, feature = "backend_filesystem"))]
 pub use self::filesystem::FileSystem;
 
+#[cfg(any(test, feature = "backend_zip"))]
+mod zip;
+#[cfg(any(test, feature = "backend_zip"))]
+pub use self::zip::Zip;
+
+#[cfg(any(test, feature = "backend_pack"))]
+mod pack;
+#[cfg(any(test, feature = "backend_pack"))]
+pub use self::pack::{Pack, PackWriter, PackEntry, PackCompression};
+
+#[cfg(any(test, feature = "backend_verified"))]
+mod verified;
+#[cfg(any(test, feature = "backend_verified"))]
+pub use self::verified::{Verified, Manifest};
+
+#[cfg(any(test, feature = "backend_zip", feature = "backend_pack"))]
+mod archive_range;
+
+/// Seekable source of archive-based backends.
+pub trait Archive: Read + Seek + Send {}
+
+impl<T> Archive for T where T: Read + Seek + Send {}
+
+/// Size and modification time of the resource in the backend.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BackendMetadata {
+    pub len: u64,
+    /// Time of the last modification, if the backend keeps it.
+    pub modified: Option<SystemTime>,
+}
+
+/// Resource contents opened with `Backend::open`.
+pub trait Stream: Read + Seek + Send {}
+
+impl<T> Stream for T where T: Read + Seek + Send {}
+
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct BackendSyncPoint {
     instant: Instant,
@@ -35,19 +83,70 @@ pub trait Backend: Send + Sync {
     fn can_write(&self) -> bool;
     fn exists(&self, path: &ResourcePath) -> bool;
 
+    /// Returns paths of all resources in the directory and its subdirectories.
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf>;
+
     fn notify_changes_synced(&mut self, point: BackendSyncPoint);
     fn new_changes(&mut self) -> Option<BackendSyncPoint>;
 
-    fn read_into(&mut self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error>;
-    fn read_vec(&mut self, path: &ResourcePath) -> Result<Vec<u8>, Error> {
+    /// Returns paths that were modified, created or removed outside of this backend
+    /// since the last call.
+    fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        Vec::new()
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error>;
+    fn read_vec(&self, path: &ResourcePath) -> Result<Vec<u8>, Error> {
         let mut output = Vec::new();
         self.read_into(path, &mut output)?;
         Ok(output)
     }
 
+    /// Opens the resource as a stream that reads contents on demand.
+    ///
+    /// The default implementation reads the whole resource into memory, which is also the fallback
+    /// for backends that transform the contents, like `Compressed`.
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        Ok(Box::new(io::Cursor::new(self.read_vec(path)?)))
+    }
+
+    /// Reads up to `len` bytes starting at `offset`, less if the resource ends earlier.
+    fn read_range(&self, path: &ResourcePath, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+        let mut stream = self.open(path)?;
+        stream.seek(SeekFrom::Start(offset))?;
+
+        let mut output = Vec::new();
+        stream.take(len as u64).read_to_end(&mut output)?;
+        Ok(output)
+    }
+
     fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error>;
     fn write(&mut self, path: &ResourcePath, mut value: &[u8]) -> Result<(), Error> {
         self.write_from(path, &mut value)?;
         Ok(())
     }
+
+    /// Removes the resource, backends that can not write return `NotWritable`.
+    fn delete(&mut self, _path: &ResourcePath) -> Result<(), Error> {
+        Err(Error::NotWritable)
+    }
+
+    /// Moves the resource to another path in this backend.
+    ///
+    /// The default implementation writes the contents to the new path and deletes the old one.
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        let data = self.read_vec(from)?;
+        self.write(to, &data)?;
+        self.delete(from)
+    }
+
+    /// Returns the size and modification time of the resource.
+    ///
+    /// The default implementation reads the whole resource to find out the size.
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        Ok(BackendMetadata {
+            len: self.read_vec(path)?.len() as u64,
+            modified: None,
+        })
+    }
 }
\ No newline at end of file
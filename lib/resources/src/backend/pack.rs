@@ -0,0 +1,532 @@
+use failure;
+use backend::{Archive, Backend, BackendMetadata, BackendSyncPoint, Stream, content_hash};
+use backend::archive_range::ArchiveRange;
+use backend::inflate::{inflate_with_limit, TINFLStatus};
+use backend::miniz::{compress_zlib, MinizError};
+use std::collections::HashMap;
+use std::fs;
+use std::hash::BuildHasherDefault;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use twox_hash::XxHash;
+use {ResourcePath, ResourcePathBuf, Error};
+
+const MAGIC: &[u8; 4] = b"RPAK";
+const VERSION: u16 = 1;
+const HEADER_LEN: u64 = 16;
+
+/// Compression of a single pack entry.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PackCompression {
+    None,
+    /// Zlib, the same codec as `Codec::Zlib` of the `Compressed` backend.
+    Zlib { level: u8 },
+}
+
+/// Location and checksum of a single file in the pack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackEntry {
+    pub compressed: bool,
+    /// Offset of contents from the start of the pack.
+    pub offset: u64,
+    pub stored_len: u64,
+    pub len: u64,
+    /// XxHash of uncompressed contents.
+    pub hash: u64,
+}
+
+/// Read-only backend that serves resources from a pack file created with `PackWriter`.
+///
+/// The pack starts with a header that points to an index of entries at the end of the file:
+///
+/// ```text
+/// header: "RPAK", version: u16, reserved: u16, index offset: u64
+/// data:   entry contents, each either stored or zlib-compressed
+/// index:  entry count: u32, then for every entry
+///         path length: u16, path, compressed: u8, offset: u64, stored length: u64, length: u64, hash: u64
+/// ```
+///
+/// All numbers are little-endian.
+pub struct Pack {
+    archive: Arc<Mutex<Box<Archive>>>,
+    start: u64,
+    entries: HashMap<ResourcePathBuf, PackEntry, BuildHasherDefault<XxHash>>,
+}
+
+impl Pack {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Pack, Error> {
+        Pack::new(fs::File::open(path)?)
+    }
+
+    /// Reads the pack that starts at the current position of the archive.
+    pub fn new<A: Archive + 'static>(mut archive: A) -> Result<Pack, Error> {
+        let start = archive.seek(SeekFrom::Current(0))?;
+        let entries = read_index(&mut archive, start)?;
+
+        Ok(Pack {
+            archive: Arc::new(Mutex::new(Box::new(archive))),
+            start,
+            entries,
+        })
+    }
+
+    /// Returns all entries sorted by path.
+    pub fn entries(&self) -> Vec<(&ResourcePath, &PackEntry)> {
+        let mut entries: Vec<_> = self.entries.iter()
+            .map(|(path, entry)| (path.as_ref(), entry))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    /// Reads the entry and checks its contents against the length and hash in the index.
+    pub fn verify(&self, path: &ResourcePath) -> Result<(), Error> {
+        let data = self.read_vec(path)?;
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+
+        let actual = content_hash(&data);
+        if data.len() as u64 != entry.len || actual != entry.hash {
+            return Err(Error::HashMismatch { path: path.into(), expected: entry.hash, actual });
+        }
+
+        Ok(())
+    }
+
+    fn entry(&self, path: &ResourcePath) -> Option<&PackEntry> {
+        self.entries.get::<ResourcePath>(path.as_clean_str().as_ref())
+    }
+}
+
+impl Backend for Pack {
+    fn can_write(&self) -> bool {
+        false
+    }
+
+    fn exists(&self, path: &ResourcePath) -> bool {
+        self.entry(path).is_some()
+    }
+
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        let dir = dir.as_clean_str();
+        self.entries.keys()
+            .filter(|path| dir.is_empty() || {
+                let path = path.as_clean_str();
+                path.starts_with(dir) && path[dir.len()..].starts_with('/')
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn notify_changes_synced(&mut self, _point: BackendSyncPoint) {}
+
+    fn new_changes(&mut self) -> Option<BackendSyncPoint> {
+        None
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+
+        let mut stored = vec![0; entry.stored_len as usize];
+        {
+            let mut archive = self.archive.lock().expect("failed to lock Pack archive");
+            archive.seek(SeekFrom::Start(self.start + entry.offset))?;
+            archive.read_exact(&mut stored)?;
+        }
+
+        if entry.compressed {
+            let size_mismatch = || invalid_data(format!("size of {} does not match the pack index", path));
+            let data = inflate_with_limit(&stored, entry.len as usize, true)
+                .map_err(|e| match e {
+                    TINFLStatus::HasMoreOutput => size_mismatch(),
+                    e => Error::FailedToDecode {
+                        path: path.into(),
+                        inner: failure::Error::from(MinizError::ErrorCode(e)),
+                    },
+                })?;
+            if data.len() as u64 != entry.len {
+                return Err(size_mismatch());
+            }
+            output.write_all(&data)?;
+        } else {
+            output.write_all(&stored)?;
+        }
+
+        Ok(())
+    }
+
+    /// Uncompressed entries are streamed from the pack, compressed entries are decompressed into memory.
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+        if entry.compressed {
+            return Ok(Box::new(io::Cursor::new(self.read_vec(path)?)));
+        }
+
+        Ok(Box::new(ArchiveRange::new(self.archive.clone(), self.start + entry.offset, entry.stored_len)))
+    }
+
+    fn write_from(&mut self, _path: &ResourcePath, _buffer: &mut io::Read) -> Result<(), Error> {
+        Err(Error::NotWritable)
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+        Ok(BackendMetadata {
+            len: entry.len,
+            modified: None,
+        })
+    }
+}
+
+/// Writes a pack file that can be read by the `Pack` backend.
+pub struct PackWriter<W> where W: Write + Seek {
+    output: W,
+    start: u64,
+    offset: u64,
+    entries: Vec<(ResourcePathBuf, PackEntry)>,
+}
+
+impl<W> PackWriter<W> where W: Write + Seek {
+    /// Starts a pack at the current position of the output.
+    pub fn new(mut output: W) -> Result<PackWriter<W>, Error> {
+        let start = output.seek(SeekFrom::Current(0))?;
+        // index offset is not known yet, it is written when the pack is finished
+        write_header(&mut output, 0)?;
+
+        Ok(PackWriter {
+            output,
+            start,
+            offset: HEADER_LEN,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Appends the file to the pack.
+    ///
+    /// Compressed entries are stored uncompressed if the compression does not make them smaller.
+    /// Fails with `InvalidData` if the path is longer than the index can store.
+    pub fn add(&mut self, path: &ResourcePath, data: &[u8], compression: PackCompression) -> Result<&PackEntry, Error> {
+        let clean_path: &ResourcePath = path.as_clean_str().into();
+        if clean_path.as_clean_str().len() > u16::max_value() as usize {
+            return Err(invalid_data(format!("path is longer than {} bytes", u16::max_value())));
+        }
+
+        let compressed = match compression {
+            PackCompression::None => None,
+            PackCompression::Zlib { level } => Some(compress_zlib(data, level))
+                .filter(|compressed| compressed.len() < data.len()),
+        };
+
+        let entry = PackEntry {
+            compressed: compressed.is_some(),
+            offset: self.offset,
+            stored_len: compressed.as_ref().map(|c| c.len()).unwrap_or(data.len()) as u64,
+            len: data.len() as u64,
+            hash: content_hash(data),
+        };
+
+        self.output.write_all(compressed.as_ref().map(|c| &c[..]).unwrap_or(data))?;
+        self.offset += entry.stored_len;
+
+        self.entries.push((clean_path.into(), entry));
+
+        Ok(&self.entries[self.entries.len() - 1].1)
+    }
+
+    /// Writes the index and returns the output.
+    pub fn finish(mut self) -> Result<W, Error> {
+        let index_offset = self.offset;
+
+        write_u32(&mut self.output, self.entries.len() as u32)?;
+        for &(ref path, ref entry) in &self.entries {
+            let path = path.as_clean_str().as_bytes();
+            write_u16(&mut self.output, path.len() as u16)?;
+            self.output.write_all(path)?;
+            self.output.write_all(&[entry.compressed as u8])?;
+            write_u64(&mut self.output, entry.offset)?;
+            write_u64(&mut self.output, entry.stored_len)?;
+            write_u64(&mut self.output, entry.len)?;
+            write_u64(&mut self.output, entry.hash)?;
+        }
+
+        self.output.seek(SeekFrom::Start(self.start))?;
+        write_header(&mut self.output, index_offset)?;
+        self.output.seek(SeekFrom::End(0))?;
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+}
+
+fn read_index(archive: &mut Archive, start: u64) -> Result<HashMap<ResourcePathBuf, PackEntry, BuildHasherDefault<XxHash>>, Error> {
+    let mut header = [0; HEADER_LEN as usize];
+    archive.read_exact(&mut header)
+        .map_err(|_| invalid_data("pack header is too short".into()))?;
+
+    if &header[0..4] != MAGIC {
+        return Err(invalid_data("not a resource pack".into()));
+    }
+    let version = u16_at(&header, 4);
+    if version != VERSION {
+        return Err(invalid_data(format!("unsupported resource pack version {}", version)));
+    }
+
+    let index_offset = u64_at(&header, 8);
+    let index_start = start.checked_add(index_offset)
+        .ok_or_else(|| invalid_data("pack index offset is out of range".into()))?;
+    archive.seek(SeekFrom::Start(index_start))?;
+    let mut index = Vec::new();
+    archive.read_to_end(&mut index)?;
+
+    let corrupted = || invalid_data("pack index is corrupted".into());
+
+    if index.len() < 4 {
+        return Err(corrupted());
+    }
+    let count = u32_at(&index, 0);
+
+    let mut entries = HashMap::default();
+    let mut offset = 4;
+
+    for _ in 0..count {
+        if offset + 2 > index.len() {
+            return Err(corrupted());
+        }
+        let path_len = u16_at(&index, offset) as usize;
+        offset += 2;
+
+        if offset + path_len + 33 > index.len() {
+            return Err(corrupted());
+        }
+        let path = String::from_utf8(index[offset..offset + path_len].to_vec())
+            .map_err(|_| corrupted())?;
+        offset += path_len;
+
+        let entry = PackEntry {
+            compressed: index[offset] != 0,
+            offset: u64_at(&index, offset + 1),
+            stored_len: u64_at(&index, offset + 9),
+            len: u64_at(&index, offset + 17),
+            hash: u64_at(&index, offset + 25),
+        };
+        offset += 33;
+
+        // contents are read into memory of this length, so it has to fit in the data before the index
+        let fits = entry.offset >= HEADER_LEN && entry.offset.checked_add(entry.stored_len)
+            .map(|end| end <= index_offset)
+            .unwrap_or(false);
+        if !fits {
+            return Err(corrupted());
+        }
+
+        entries.insert(ResourcePathBuf::from(path), entry);
+    }
+
+    Ok(entries)
+}
+
+fn write_header(output: &mut Write, index_offset: u64) -> io::Result<()> {
+    output.write_all(MAGIC)?;
+    write_u16(output, VERSION)?;
+    write_u16(output, 0)?;
+    write_u64(output, index_offset)
+}
+
+fn write_u16(output: &mut Write, value: u16) -> io::Result<()> {
+    output.write_all(&[value as u8, (value >> 8) as u8])
+}
+
+fn write_u32(output: &mut Write, value: u32) -> io::Result<()> {
+    write_u16(output, value as u16)?;
+    write_u16(output, (value >> 16) as u16)
+}
+
+fn write_u64(output: &mut Write, value: u64) -> io::Result<()> {
+    write_u32(output, value as u32)?;
+    write_u32(output, (value >> 32) as u32)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    data[offset] as u16 | (data[offset + 1] as u16) << 8
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u16_at(data, offset) as u32 | (u16_at(data, offset + 2) as u32) << 16
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u32_at(data, offset) as u64 | (u32_at(data, offset + 4) as u64) << 32
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::InvalidData(message)
+}
+
+#[cfg(test)]
+mod test {
+    use backend::{Backend, Pack, PackWriter, PackCompression};
+    use {Error, ResourcePathBuf};
+    use std::io::Cursor;
+
+    fn pack(files: &[(&str, &[u8], PackCompression)]) -> Vec<u8> {
+        let mut writer = PackWriter::new(Cursor::new(Vec::new())).unwrap();
+        for &(path, data, compression) in files {
+            writer.add(path.into(), data, compression).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_can_read_stored_and_compressed_entries() {
+        let long = [b'a'; 1000];
+        let be = Pack::new(Cursor::new(pack(&[
+            ("stored.txt", b"hello stored", PackCompression::None),
+            ("shaders/compressed.txt", &long, PackCompression::Zlib { level: 6 }),
+        ]))).unwrap();
+
+        assert!(!be.can_write());
+        assert!(be.exists("stored.txt".into()));
+        assert!(!be.exists("missing.txt".into()));
+
+        assert_eq!(b"hello stored", &be.read_vec("stored.txt".into()).unwrap()[..]);
+        assert_eq!(&long[..], &be.read_vec("shaders/compressed.txt".into()).unwrap()[..]);
+        assert_eq!(Err(Error::NotFound), be.read_vec("missing.txt".into()));
+        assert_eq!(vec![ResourcePathBuf::from(String::from("shaders/compressed.txt"))], be.list("shaders".into()));
+
+        let entries = be.entries();
+        assert_eq!(2, entries.len());
+        assert!(entries[0].1.compressed);
+        assert!(entries[0].1.stored_len < 1000);
+        assert!(!entries[1].1.compressed);
+    }
+
+    #[test]
+    fn test_entries_that_do_not_shrink_are_stored() {
+        let be = Pack::new(Cursor::new(pack(&[
+            ("x", b"abc", PackCompression::Zlib { level: 6 }),
+        ]))).unwrap();
+
+        assert!(!be.entries()[0].1.compressed);
+    }
+
+    #[test]
+    fn test_verify_detects_corrupted_contents() {
+        let mut data = pack(&[
+            ("a", b"hello", PackCompression::None),
+            ("b", b"world", PackCompression::None),
+        ]);
+
+        Pack::new(Cursor::new(data.clone())).unwrap().verify("a".into()).unwrap();
+
+        // contents of "b" start after the header and contents of "a"
+        data[16 + 5] = b'W';
+        let be = Pack::new(Cursor::new(data)).unwrap();
+
+        be.verify("a".into()).unwrap();
+        match be.verify("b".into()) {
+            Err(Error::HashMismatch { ref path, .. }) if path.as_clean_str() == "b" => (),
+            other => panic!("expected HashMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pack_can_be_read_at_non_zero_offset() {
+        let mut data = b"prefix".to_vec();
+        data.extend(pack(&[("a", b"hello", PackCompression::None)]));
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(6);
+        let be = Pack::new(cursor).unwrap();
+
+        assert_eq!(b"hello", &be.read_vec("a".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_ranged_reads_of_stored_and_compressed_entries() {
+        let mut long = vec![b'a'; 1000];
+        long.extend(b"end");
+        let mut data = b"prefix".to_vec();
+        data.extend(pack(&[
+            ("stored.txt", b"hello stored", PackCompression::None),
+            ("compressed.txt", &long, PackCompression::Zlib { level: 6 }),
+        ]));
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(6);
+        let be = Pack::new(cursor).unwrap();
+
+        assert_eq!(b"stored", &be.read_range("stored.txt".into(), 6, 100).unwrap()[..]);
+        assert_eq!(b"end", &be.read_range("compressed.txt".into(), 1000, 3).unwrap()[..]);
+        assert_eq!(Err(Error::NotFound), be.read_range("missing.txt".into(), 0, 1));
+    }
+
+    #[test]
+    fn test_entries_outside_of_the_data_fail_to_open() {
+        let data = pack(&[("a", b"hello", PackCompression::None)]);
+        // index: entry count, path length, path "a", compressed, offset, stored length
+        let stored_len_at = 16 + 5 + 4 + 2 + 1 + 1 + 8;
+
+        let mut corrupted = data.clone();
+        corrupted[stored_len_at + 7] = 0xff;
+        match Pack::new(Cursor::new(corrupted)) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other.map(|_| ())),
+        }
+
+        let mut corrupted = data.clone();
+        corrupted[stored_len_at] = 6;
+        assert!(Pack::new(Cursor::new(corrupted)).is_err());
+    }
+
+    #[test]
+    fn test_index_offset_out_of_range_fails_to_open() {
+        let mut data = b"prefix".to_vec();
+        data.extend(pack(&[("a", b"hello", PackCompression::None)]));
+        // index offset is the last field of the header
+        for byte in &mut data[6 + 8..6 + 16] {
+            *byte = 0xff;
+        }
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(6);
+        match Pack::new(cursor) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_compressed_entries_are_not_inflated_over_their_length() {
+        let long = vec![b'a'; 1 << 20];
+        let data = pack(&[("bomb", &long, PackCompression::Zlib { level: 6 })]);
+        let stored_len = Pack::new(Cursor::new(data.clone())).unwrap().entries()[0].1.stored_len as usize;
+        // index: entry count, path length, path "bomb", compressed, offset, stored length, length
+        let len_at = 16 + stored_len + 4 + 2 + 4 + 1 + 8 + 8;
+
+        let mut corrupted = data.clone();
+        corrupted[len_at..len_at + 8].copy_from_slice(&[100, 0, 0, 0, 0, 0, 0, 0]);
+        let be = Pack::new(Cursor::new(corrupted)).unwrap();
+        match be.read_vec("bomb".into()) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other.map(|data| data.len())),
+        }
+        assert_eq!(long, Pack::new(Cursor::new(data)).unwrap().read_vec("bomb".into()).unwrap());
+    }
+
+    #[test]
+    fn test_too_long_paths_are_not_added() {
+        let mut writer = PackWriter::new(Cursor::new(Vec::new())).unwrap();
+        let long = "a".repeat(70000);
+
+        match writer.add(long.as_str().into(), b"hello", PackCompression::None) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other),
+        }
+        writer.add("a".into(), b"hello", PackCompression::None).unwrap();
+    }
+
+    #[test]
+    fn test_not_a_pack_fails_to_open() {
+        assert!(Pack::new(Cursor::new(b"hello world, not a pack".to_vec())).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use backend::{Backend, BackendMetadata, BackendSyncPoint, Stream, content_hash};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use {ResourcePath, ResourcePathBuf, Error};
+
+/// Expected `content_hash` of resources.
+///
+/// The text form has one line per resource: the hash as 16 hex digits, a space and the path.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Manifest {
+    hashes: BTreeMap<ResourcePathBuf, u64>,
+}
+
+impl Manifest {
+    pub fn new() -> Manifest {
+        Manifest::default()
+    }
+
+    /// Computes the manifest of all resources in the directory of the backend.
+    pub fn from_backend(backend: &Backend, dir: &ResourcePath) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::new();
+        for path in backend.list(dir) {
+            let hash = content_hash(&backend.read_vec(&path)?);
+            manifest.insert(&path, hash);
+        }
+        Ok(manifest)
+    }
+
+    /// Reads the manifest from the text form.
+    pub fn parse(text: &str) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, ' ');
+            let hash = parts.next()
+                .and_then(|hash| u64::from_str_radix(hash, 16).ok());
+            match (hash, parts.next()) {
+                (Some(hash), Some(path)) => manifest.insert(path.trim().as_ref(), hash),
+                _ => return Err(Error::InvalidData(format!("invalid manifest line {}", index + 1))),
+            }
+        }
+        Ok(manifest)
+    }
+
+    pub fn with<P: AsRef<ResourcePath>>(mut self, path: P, hash: u64) -> Manifest {
+        self.insert(path.as_ref(), hash);
+        self
+    }
+
+    pub fn insert(&mut self, path: &ResourcePath, hash: u64) {
+        self.hashes.insert(key(path), hash);
+    }
+
+    pub fn remove(&mut self, path: &ResourcePath) -> Option<u64> {
+        self.hashes.remove(&key(path))
+    }
+
+    /// Returns the expected hash, or `None` if the resource is not in the manifest.
+    pub fn hash(&self, path: &ResourcePath) -> Option<u64> {
+        self.hashes.get(&key(path)).cloned()
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for (path, hash) in &self.hashes {
+            writeln!(f, "{:016x} {}", hash, path)?;
+        }
+        Ok(())
+    }
+}
+
+fn key(path: &ResourcePath) -> ResourcePathBuf {
+    ResourcePathBuf::from(String::from(path.as_clean_str()))
+}
+
+/// Wrapper that checks contents read from the inner backend against a `Manifest`.
+///
+/// Reads of resources with a different hash fail with `Error::HashMismatch`. Resources that are
+/// not in the manifest are read without checks. Contents written through the wrapper become the
+/// expected contents.
+///
+/// Streams and ranged reads of resources in the manifest read the whole resource into memory.
+#[derive(Debug)]
+pub struct Verified<T> where T: Backend {
+    inner: T,
+    manifest: Manifest,
+}
+
+impl<T> Verified<T> where T: Backend {
+    pub fn new(inner: T, manifest: Manifest) -> Verified<T> {
+        Verified {
+            inner,
+            manifest,
+        }
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    fn read_verified(&self, path: &ResourcePath, expected: u64) -> Result<Vec<u8>, Error> {
+        let data = self.inner.read_vec(path)?;
+        let actual = content_hash(&data);
+        if actual != expected {
+            return Err(Error::HashMismatch { path: path.into(), expected, actual });
+        }
+        Ok(data)
+    }
+}
+
+impl<T> Backend for Verified<T> where T: Backend {
+    fn can_write(&self) -> bool {
+        self.inner.can_write()
+    }
+
+    fn exists(&self, path: &ResourcePath) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        self.inner.list(dir)
+    }
+
+    fn notify_changes_synced(&mut self, point: BackendSyncPoint) {
+        self.inner.notify_changes_synced(point);
+    }
+
+    fn new_changes(&mut self) -> Option<BackendSyncPoint> {
+        self.inner.new_changes()
+    }
+
+    fn take_modified_paths(&mut self) -> Vec<ResourcePathBuf> {
+        self.inner.take_modified_paths()
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        match self.manifest.hash(path) {
+            Some(expected) => Ok(output.write_all(&self.read_verified(path, expected)?)?),
+            None => self.inner.read_into(path, output),
+        }
+    }
+
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        match self.manifest.hash(path) {
+            Some(expected) => Ok(Box::new(io::Cursor::new(self.read_verified(path, expected)?))),
+            None => self.inner.open(path),
+        }
+    }
+
+    fn read_range(&self, path: &ResourcePath, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+        match self.manifest.hash(path) {
+            Some(expected) => {
+                let data = self.read_verified(path, expected)?;
+                let start = ::std::cmp::min(offset, data.len() as u64) as usize;
+                let end = ::std::cmp::min(start.saturating_add(len), data.len());
+                Ok(data[start..end].to_vec())
+            },
+            None => self.inner.read_range(path, offset, len),
+        }
+    }
+
+    fn write_from(&mut self, path: &ResourcePath, buffer: &mut io::Read) -> Result<(), Error> {
+        let mut data = Vec::new();
+        buffer.read_to_end(&mut data)?;
+        self.inner.write(path, &data)?;
+        self.manifest.insert(path, content_hash(&data));
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &ResourcePath) -> Result<(), Error> {
+        self.inner.delete(path)?;
+        self.manifest.remove(path);
+        Ok(())
+    }
+
+    fn rename(&mut self, from: &ResourcePath, to: &ResourcePath) -> Result<(), Error> {
+        self.inner.rename(from, to)?;
+        match self.manifest.remove(from) {
+            Some(hash) => self.manifest.insert(to, hash),
+            None => { self.manifest.remove(to); },
+        }
+        Ok(())
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        self.inner.metadata(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use backend::{Backend, InMemory, Verified, Manifest, content_hash};
+    use Error;
+
+    fn assert_mismatch<T: ::std::fmt::Debug>(result: Result<T, Error>) {
+        match result {
+            Err(Error::HashMismatch { ref path, expected, actual }) => {
+                assert_eq!("x", path.as_clean_str());
+                assert_eq!(content_hash(b"hello"), expected);
+                assert_eq!(content_hash(b"hellO"), actual);
+            },
+            other => panic!("expected HashMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reads_matching_contents() {
+        let be = Verified::new(
+            InMemory::new().with("x", b"hello").with("unlisted", b"world"),
+            Manifest::new().with("x", content_hash(b"hello")),
+        );
+
+        assert_eq!(b"hello", &be.read_vec("x".into()).unwrap()[..]);
+        assert_eq!(b"ell", &be.read_range("x".into(), 1, 3).unwrap()[..]);
+        assert_eq!(b"llo", &be.read_range("x".into(), 2, usize::max_value()).unwrap()[..]);
+        assert_eq!(b"world", &be.read_vec("unlisted".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_fails_on_mismatched_contents() {
+        let be = Verified::new(
+            InMemory::new().with("x", b"hellO"),
+            Manifest::new().with("x", content_hash(b"hello")),
+        );
+
+        assert_mismatch(be.read_vec("x".into()));
+        assert_mismatch(be.open("x".into()).map(|_| ()));
+        assert_mismatch(be.read_range("x".into(), 0, 1));
+    }
+
+    #[test]
+    fn test_written_contents_become_expected_contents() {
+        let mut be = Verified::new(
+            InMemory::new().with("x", b"hellO"),
+            Manifest::new().with("x", content_hash(b"hello")),
+        );
+
+        be.write("x".into(), b"fixed").unwrap();
+        assert_eq!(b"fixed", &be.read_vec("x".into()).unwrap()[..]);
+
+        be.rename("x".into(), "y".into()).unwrap();
+        assert_eq!(None, be.manifest().hash("x".into()));
+        assert_eq!(Some(content_hash(b"fixed")), be.manifest().hash("y".into()));
+    }
+
+    #[test]
+    fn test_manifest_text_form() {
+        let be = InMemory::new().with("a/b", b"hello").with("c", b"world");
+        let manifest = Manifest::from_backend(&be, "".into()).unwrap();
+        let text = manifest.to_string();
+
+        assert_eq!(format!("{:016x} a/b\n{:016x} c\n", content_hash(b"hello"), content_hash(b"world")), text);
+        assert_eq!(manifest, Manifest::parse(&text).unwrap());
+        assert!(Manifest::parse("not a hash").is_err());
+    }
+}
@@ -0,0 +1,548 @@
+extern crate miniz_oxide as miniz;
+
+use failure;
+use backend::{Archive, Backend, BackendMetadata, BackendSyncPoint, Stream};
+use backend::archive_range::ArchiveRange;
+use backend::inflate::inflate_with_limit;
+use self::miniz::inflate::TINFLStatus;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::BuildHasherDefault;
+use std::io::{self, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use twox_hash::XxHash;
+use {ResourcePath, ResourcePathBuf, Error};
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const CENTRAL_DIRECTORY_HEADER_LEN: usize = 46;
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const LOCAL_FILE_HEADER_LEN: usize = 30;
+const MAX_COMMENT_LEN: usize = 0xffff;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+struct Entry {
+    method: u16,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_header_offset: u64,
+}
+
+/// Read-only backend that serves resources from entries of a zip archive.
+///
+/// Supports stored and deflated entries. Stored entries are streamed from the archive, while
+/// deflated entries are decompressed into memory when opened.
+///
+/// Entries read into memory are checked against the CRC-32 from the archive, streamed entries
+/// are not, because they may be read only in part.
+pub struct Zip {
+    archive: Arc<Mutex<Box<Archive>>>,
+    entries: HashMap<ResourcePathBuf, Entry, BuildHasherDefault<XxHash>>,
+}
+
+impl Zip {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Zip, Error> {
+        Zip::new(fs::File::open(path)?)
+    }
+
+    pub fn new<A: Archive + 'static>(mut archive: A) -> Result<Zip, Error> {
+        let entries = read_central_directory(&mut archive)?;
+
+        Ok(Zip {
+            archive: Arc::new(Mutex::new(Box::new(archive))),
+            entries,
+        })
+    }
+
+    fn entry(&self, path: &ResourcePath) -> Option<&Entry> {
+        self.entries.get::<ResourcePath>(path.as_clean_str().as_ref())
+    }
+}
+
+impl Backend for Zip {
+    fn can_write(&self) -> bool {
+        false
+    }
+
+    fn exists(&self, path: &ResourcePath) -> bool {
+        self.entry(path).is_some()
+    }
+
+    fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        let dir = dir.as_clean_str();
+        self.entries.keys()
+            .filter(|path| dir.is_empty() || {
+                let path = path.as_clean_str();
+                path.starts_with(dir) && path[dir.len()..].starts_with('/')
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn notify_changes_synced(&mut self, _point: BackendSyncPoint) {}
+
+    fn new_changes(&mut self) -> Option<BackendSyncPoint> {
+        None
+    }
+
+    fn read_into(&self, path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+
+        let compressed = {
+            let mut archive = self.archive.lock().expect("failed to lock Zip archive");
+            read_entry_data(&mut **archive, entry)?
+        };
+
+        let size_mismatch = || invalid_data(format!("size of {} does not match the archive", path));
+        let data = match entry.method {
+            METHOD_STORED => compressed,
+            METHOD_DEFLATED => inflate_with_limit(&compressed, entry.uncompressed_size as usize, false)
+                .map_err(|e| match e {
+                    TINFLStatus::HasMoreOutput => size_mismatch(),
+                    e => Error::FailedToDecode {
+                        path: path.into(),
+                        inner: failure::err_msg(format!("failed to inflate: {:?}", e)),
+                    },
+                })?,
+            method => return Err(invalid_data(format!("unsupported compression method {} for {}", method, path))),
+        };
+
+        if data.len() as u64 != entry.uncompressed_size {
+            return Err(size_mismatch());
+        }
+        let actual = crc32(&data);
+        if actual != entry.crc32 {
+            return Err(Error::HashMismatch { path: path.into(), expected: entry.crc32 as u64, actual: actual as u64 });
+        }
+
+        output.write_all(&data)?;
+        Ok(())
+    }
+
+    fn open(&self, path: &ResourcePath) -> Result<Box<Stream>, Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+        if entry.method != METHOD_STORED {
+            return Ok(Box::new(io::Cursor::new(self.read_vec(path)?)));
+        }
+
+        let data_offset = {
+            let mut archive = self.archive.lock().expect("failed to lock Zip archive");
+            entry_data_offset(&mut **archive, entry)?
+        };
+
+        Ok(Box::new(ArchiveRange::new(self.archive.clone(), data_offset, entry.compressed_size)))
+    }
+
+    fn write_from(&mut self, _path: &ResourcePath, _buffer: &mut io::Read) -> Result<(), Error> {
+        Err(Error::NotWritable)
+    }
+
+    fn metadata(&self, path: &ResourcePath) -> Result<BackendMetadata, Error> {
+        let entry = self.entry(path).ok_or(Error::NotFound)?;
+        Ok(BackendMetadata {
+            len: entry.uncompressed_size,
+            modified: None,
+        })
+    }
+}
+
+fn read_central_directory(archive: &mut Archive) -> Result<HashMap<ResourcePathBuf, Entry, BuildHasherDefault<XxHash>>, Error> {
+    let archive_len = archive.seek(SeekFrom::End(0))?;
+
+    // end of central directory record is followed by a comment of unknown length
+    let tail_len = ::std::cmp::min(archive_len, (END_OF_CENTRAL_DIRECTORY_LEN + MAX_COMMENT_LEN) as u64);
+    archive.seek(SeekFrom::Start(archive_len - tail_len))?;
+    let mut tail = vec![0; tail_len as usize];
+    archive.read_exact(&mut tail)?;
+
+    let eocd = (0..(tail.len() + 1).saturating_sub(END_OF_CENTRAL_DIRECTORY_LEN))
+        .rev()
+        .find(|i| u32_at(&tail, *i) == END_OF_CENTRAL_DIRECTORY_SIGNATURE)
+        .map(|i| &tail[i..])
+        .ok_or_else(|| invalid_data("zip end of central directory not found".into()))?;
+
+    let entry_count = u16_at(eocd, 10) as usize;
+    let directory_len = u32_at(eocd, 12) as usize;
+    let directory_offset = u32_at(eocd, 16) as u64;
+
+    if entry_count == 0xffff || directory_offset == 0xffffffff {
+        return Err(invalid_data("zip64 archives are not supported".into()));
+    }
+
+    if directory_offset + directory_len as u64 > archive_len {
+        return Err(invalid_data("zip central directory is outside of the archive".into()));
+    }
+    archive.seek(SeekFrom::Start(directory_offset))?;
+    let mut directory = vec![0; directory_len];
+    archive.read_exact(&mut directory)?;
+
+    let mut entries = HashMap::default();
+    let mut offset = 0;
+
+    for _ in 0..entry_count {
+        if offset + CENTRAL_DIRECTORY_HEADER_LEN > directory.len()
+            || u32_at(&directory, offset) != CENTRAL_DIRECTORY_HEADER_SIGNATURE {
+            return Err(invalid_data("zip central directory is corrupted".into()));
+        }
+
+        let header = &directory[offset..];
+        let name_len = u16_at(header, 28) as usize;
+        let extra_len = u16_at(header, 30) as usize;
+        let comment_len = u16_at(header, 32) as usize;
+
+        let name_end = CENTRAL_DIRECTORY_HEADER_LEN + name_len;
+        if name_end > header.len() {
+            return Err(invalid_data("zip central directory is corrupted".into()));
+        }
+
+        let name = String::from_utf8_lossy(&header[CENTRAL_DIRECTORY_HEADER_LEN..name_end])
+            .replace('\\', "/");
+
+        if !name.ends_with('/') {
+            let path: &ResourcePath = name.as_str().into();
+            entries.insert(ResourcePathBuf::from(path.as_clean_str().as_ref()), Entry {
+                method: u16_at(header, 10),
+                crc32: u32_at(header, 16),
+                compressed_size: u32_at(header, 20) as u64,
+                uncompressed_size: u32_at(header, 24) as u64,
+                local_header_offset: u32_at(header, 42) as u64,
+            });
+        }
+
+        offset += name_end + extra_len + comment_len;
+    }
+
+    Ok(entries)
+}
+
+/// Returns the offset of entry contents, which follow the local header.
+///
+/// Fails if the contents do not fit in the archive, so that their size can be trusted.
+fn entry_data_offset(archive: &mut Archive, entry: &Entry) -> Result<u64, Error> {
+    let archive_len = archive.seek(SeekFrom::End(0))?;
+    archive.seek(SeekFrom::Start(entry.local_header_offset))?;
+    let mut header = [0; LOCAL_FILE_HEADER_LEN];
+    archive.read_exact(&mut header)?;
+
+    if u32_at(&header, 0) != LOCAL_FILE_HEADER_SIGNATURE {
+        return Err(invalid_data("zip local file header is corrupted".into()));
+    }
+
+    let name_len = u16_at(&header, 26) as u64;
+    let extra_len = u16_at(&header, 28) as u64;
+    let data_offset = entry.local_header_offset + LOCAL_FILE_HEADER_LEN as u64 + name_len + extra_len;
+    if data_offset + entry.compressed_size > archive_len {
+        return Err(invalid_data("zip entry is outside of the archive".into()));
+    }
+    Ok(data_offset)
+}
+
+fn read_entry_data(archive: &mut Archive, entry: &Entry) -> Result<Vec<u8>, Error> {
+    let data_offset = entry_data_offset(archive, entry)?;
+    archive.seek(SeekFrom::Start(data_offset))?;
+
+    let mut data = vec![0; entry.compressed_size as usize];
+    archive.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// CRC-32 of zip entries, with the reversed 0xedb88320 polynomial.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+/// CRC-32 of each byte value, see `test_crc32_table_matches_polynomial`.
+static CRC32_TABLE: [u32; 256] = [
+    0x00000000, 0x77073096, 0xee0e612c, 0x990951ba, 0x076dc419, 0x706af48f,
+    0xe963a535, 0x9e6495a3, 0x0edb8832, 0x79dcb8a4, 0xe0d5e91e, 0x97d2d988,
+    0x09b64c2b, 0x7eb17cbd, 0xe7b82d07, 0x90bf1d91, 0x1db71064, 0x6ab020f2,
+    0xf3b97148, 0x84be41de, 0x1adad47d, 0x6ddde4eb, 0xf4d4b551, 0x83d385c7,
+    0x136c9856, 0x646ba8c0, 0xfd62f97a, 0x8a65c9ec, 0x14015c4f, 0x63066cd9,
+    0xfa0f3d63, 0x8d080df5, 0x3b6e20c8, 0x4c69105e, 0xd56041e4, 0xa2677172,
+    0x3c03e4d1, 0x4b04d447, 0xd20d85fd, 0xa50ab56b, 0x35b5a8fa, 0x42b2986c,
+    0xdbbbc9d6, 0xacbcf940, 0x32d86ce3, 0x45df5c75, 0xdcd60dcf, 0xabd13d59,
+    0x26d930ac, 0x51de003a, 0xc8d75180, 0xbfd06116, 0x21b4f4b5, 0x56b3c423,
+    0xcfba9599, 0xb8bda50f, 0x2802b89e, 0x5f058808, 0xc60cd9b2, 0xb10be924,
+    0x2f6f7c87, 0x58684c11, 0xc1611dab, 0xb6662d3d, 0x76dc4190, 0x01db7106,
+    0x98d220bc, 0xefd5102a, 0x71b18589, 0x06b6b51f, 0x9fbfe4a5, 0xe8b8d433,
+    0x7807c9a2, 0x0f00f934, 0x9609a88e, 0xe10e9818, 0x7f6a0dbb, 0x086d3d2d,
+    0x91646c97, 0xe6635c01, 0x6b6b51f4, 0x1c6c6162, 0x856530d8, 0xf262004e,
+    0x6c0695ed, 0x1b01a57b, 0x8208f4c1, 0xf50fc457, 0x65b0d9c6, 0x12b7e950,
+    0x8bbeb8ea, 0xfcb9887c, 0x62dd1ddf, 0x15da2d49, 0x8cd37cf3, 0xfbd44c65,
+    0x4db26158, 0x3ab551ce, 0xa3bc0074, 0xd4bb30e2, 0x4adfa541, 0x3dd895d7,
+    0xa4d1c46d, 0xd3d6f4fb, 0x4369e96a, 0x346ed9fc, 0xad678846, 0xda60b8d0,
+    0x44042d73, 0x33031de5, 0xaa0a4c5f, 0xdd0d7cc9, 0x5005713c, 0x270241aa,
+    0xbe0b1010, 0xc90c2086, 0x5768b525, 0x206f85b3, 0xb966d409, 0xce61e49f,
+    0x5edef90e, 0x29d9c998, 0xb0d09822, 0xc7d7a8b4, 0x59b33d17, 0x2eb40d81,
+    0xb7bd5c3b, 0xc0ba6cad, 0xedb88320, 0x9abfb3b6, 0x03b6e20c, 0x74b1d29a,
+    0xead54739, 0x9dd277af, 0x04db2615, 0x73dc1683, 0xe3630b12, 0x94643b84,
+    0x0d6d6a3e, 0x7a6a5aa8, 0xe40ecf0b, 0x9309ff9d, 0x0a00ae27, 0x7d079eb1,
+    0xf00f9344, 0x8708a3d2, 0x1e01f268, 0x6906c2fe, 0xf762575d, 0x806567cb,
+    0x196c3671, 0x6e6b06e7, 0xfed41b76, 0x89d32be0, 0x10da7a5a, 0x67dd4acc,
+    0xf9b9df6f, 0x8ebeeff9, 0x17b7be43, 0x60b08ed5, 0xd6d6a3e8, 0xa1d1937e,
+    0x38d8c2c4, 0x4fdff252, 0xd1bb67f1, 0xa6bc5767, 0x3fb506dd, 0x48b2364b,
+    0xd80d2bda, 0xaf0a1b4c, 0x36034af6, 0x41047a60, 0xdf60efc3, 0xa867df55,
+    0x316e8eef, 0x4669be79, 0xcb61b38c, 0xbc66831a, 0x256fd2a0, 0x5268e236,
+    0xcc0c7795, 0xbb0b4703, 0x220216b9, 0x5505262f, 0xc5ba3bbe, 0xb2bd0b28,
+    0x2bb45a92, 0x5cb36a04, 0xc2d7ffa7, 0xb5d0cf31, 0x2cd99e8b, 0x5bdeae1d,
+    0x9b64c2b0, 0xec63f226, 0x756aa39c, 0x026d930a, 0x9c0906a9, 0xeb0e363f,
+    0x72076785, 0x05005713, 0x95bf4a82, 0xe2b87a14, 0x7bb12bae, 0x0cb61b38,
+    0x92d28e9b, 0xe5d5be0d, 0x7cdcefb7, 0x0bdbdf21, 0x86d3d2d4, 0xf1d4e242,
+    0x68ddb3f8, 0x1fda836e, 0x81be16cd, 0xf6b9265b, 0x6fb077e1, 0x18b74777,
+    0x88085ae6, 0xff0f6a70, 0x66063bca, 0x11010b5c, 0x8f659eff, 0xf862ae69,
+    0x616bffd3, 0x166ccf45, 0xa00ae278, 0xd70dd2ee, 0x4e048354, 0x3903b3c2,
+    0xa7672661, 0xd06016f7, 0x4969474d, 0x3e6e77db, 0xaed16a4a, 0xd9d65adc,
+    0x40df0b66, 0x37d83bf0, 0xa9bcae53, 0xdebb9ec5, 0x47b2cf7f, 0x30b5ffe9,
+    0xbdbdf21c, 0xcabac28a, 0x53b39330, 0x24b4a3a6, 0xbad03605, 0xcdd70693,
+    0x54de5729, 0x23d967bf, 0xb3667a2e, 0xc4614ab8, 0x5d681b02, 0x2a6f2b94,
+    0xb40bbe37, 0xc30c8ea1, 0x5a05df1b, 0x2d02ef8d,
+];
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    data[offset] as u16 | (data[offset + 1] as u16) << 8
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u16_at(data, offset) as u32 | (u16_at(data, offset + 2) as u32) << 16
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::InvalidData(message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{miniz, crc32, CRC32_TABLE};
+    use backend::{Backend, Zip, InMemory};
+    use {Error, Resources, ResourcePathBuf};
+    use std::io::Cursor;
+
+    fn push_u16(out: &mut Vec<u8>, value: u16) {
+        out.extend(&[value as u8, (value >> 8) as u8]);
+    }
+
+    fn push_u32(out: &mut Vec<u8>, value: u32) {
+        push_u16(out, value as u16);
+        push_u16(out, (value >> 16) as u16);
+    }
+
+    /// Builds a zip archive with the entries and their CRC-32.
+    fn zip(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut directory = Vec::new();
+
+        for &(name, data, deflate) in files {
+            let (method, stored) = if deflate {
+                (8, miniz::deflate::compress_to_vec(data, 6))
+            } else {
+                (0, data.to_vec())
+            };
+            let offset = out.len() as u32;
+
+            push_u32(&mut out, 0x04034b50);
+            push_u16(&mut out, 20);
+            push_u16(&mut out, 0);
+            push_u16(&mut out, method);
+            push_u32(&mut out, 0);
+            push_u32(&mut out, crc32(data));
+            push_u32(&mut out, stored.len() as u32);
+            push_u32(&mut out, data.len() as u32);
+            push_u16(&mut out, name.len() as u16);
+            push_u16(&mut out, 0);
+            out.extend(name.as_bytes());
+            out.extend(&stored);
+
+            push_u32(&mut directory, 0x02014b50);
+            push_u16(&mut directory, 20);
+            push_u16(&mut directory, 20);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, method);
+            push_u32(&mut directory, 0);
+            push_u32(&mut directory, crc32(data));
+            push_u32(&mut directory, stored.len() as u32);
+            push_u32(&mut directory, data.len() as u32);
+            push_u16(&mut directory, name.len() as u16);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u32(&mut directory, 0);
+            push_u32(&mut directory, offset);
+            directory.extend(name.as_bytes());
+        }
+
+        let directory_offset = out.len() as u32;
+        out.extend(&directory);
+
+        push_u32(&mut out, 0x06054b50);
+        push_u16(&mut out, 0);
+        push_u16(&mut out, 0);
+        push_u16(&mut out, files.len() as u16);
+        push_u16(&mut out, files.len() as u16);
+        push_u32(&mut out, directory.len() as u32);
+        push_u32(&mut out, directory_offset);
+        push_u16(&mut out, 0);
+
+        out
+    }
+
+    #[test]
+    fn test_can_read_stored_and_deflated_entries() {
+        let be = Zip::new(Cursor::new(zip(&[
+            ("stored.txt", b"hello stored", false),
+            ("shaders/deflated.txt", b"hello deflated hello deflated", true),
+            ("empty/", b"", false),
+        ]))).unwrap();
+
+        assert!(!be.can_write());
+        assert!(be.exists("stored.txt".into()));
+        assert!(be.exists("shaders/deflated.txt".into()));
+        assert!(!be.exists("empty".into()));
+
+        assert_eq!(b"hello stored", &be.read_vec("stored.txt".into()).unwrap()[..]);
+        assert_eq!(b"hello deflated hello deflated", &be.read_vec("shaders/deflated.txt".into()).unwrap()[..]);
+        assert_eq!(Err(Error::NotFound), be.read_vec("missing.txt".into()));
+        assert_eq!(vec![ResourcePathBuf::from(String::from("shaders/deflated.txt"))], be.list("shaders".into()));
+    }
+
+    #[test]
+    fn test_can_open_stored_and_deflated_entries() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let be = Zip::new(Cursor::new(zip(&[
+            ("stored.txt", b"hello stored", false),
+            ("deflated.txt", b"hello deflated hello deflated", true),
+        ]))).unwrap();
+
+        let mut stored = be.open("stored.txt".into()).unwrap();
+        stored.seek(SeekFrom::End(-6)).unwrap();
+        let mut end = String::new();
+        stored.read_to_string(&mut end).unwrap();
+        assert_eq!("stored", end);
+
+        assert_eq!(b"deflated", &be.read_range("deflated.txt".into(), 6, 8).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_can_not_write() {
+        let mut be = Zip::new(Cursor::new(zip(&[]))).unwrap();
+
+        match be.write("x".into(), b"hello") {
+            Err(Error::NotWritable) => (),
+            other => panic!("expected NotWritable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_crc32_matches_known_value() {
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+        assert_eq!(0, crc32(b""));
+    }
+
+    #[test]
+    fn test_crc32_table_matches_polynomial() {
+        for (i, &value) in CRC32_TABLE.iter().enumerate() {
+            let expected = (0..8).fold(i as u32, |c, _| if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 });
+            assert_eq!(expected, value);
+        }
+    }
+
+    #[test]
+    fn test_corrupted_entries_are_not_returned() {
+        let mut corrupted = zip(&[
+            ("stored.txt", b"hello stored", false),
+            ("deflated.txt", b"hello deflated hello deflated", true),
+        ]);
+        // contents of "stored.txt" follow its local header and name
+        let stored_at = 30 + "stored.txt".len();
+
+        corrupted[stored_at] = b'H';
+        let be = Zip::new(Cursor::new(corrupted)).unwrap();
+        match be.read_vec("stored.txt".into()) {
+            Err(Error::HashMismatch { .. }) => (),
+            other => panic!("expected HashMismatch, got {:?}", other),
+        }
+        assert_eq!(b"hello deflated hello deflated", &be.read_vec("deflated.txt".into()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_sizes_outside_of_the_archive_are_rejected() {
+        let data = zip(&[("stored.txt", b"hello stored", false)]);
+        let eocd_at = data.len() - 22;
+        let directory_at = eocd_at - (46 + "stored.txt".len());
+
+        let mut corrupted = data.clone();
+        corrupted[eocd_at + 15] = 0xff;
+        match Zip::new(Cursor::new(corrupted)) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other.map(|_| ())),
+        }
+
+        let mut corrupted = data.clone();
+        corrupted[directory_at + 23] = 0xff;
+        let be = Zip::new(Cursor::new(corrupted)).unwrap();
+        match be.read_vec("stored.txt".into()) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other),
+        }
+        assert!(be.open("stored.txt".into()).is_err());
+    }
+
+    #[test]
+    fn test_entries_are_not_inflated_over_their_size() {
+        let data = zip(&[("bomb.txt", &vec![0; 1 << 20], true)]);
+        let directory_at = data.len() - 22 - (46 + "bomb.txt".len());
+
+        let mut corrupted = data.clone();
+        corrupted[directory_at + 24..directory_at + 28].copy_from_slice(&[100, 0, 0, 0]);
+        let be = Zip::new(Cursor::new(corrupted)).unwrap();
+        match be.read_vec("bomb.txt".into()) {
+            Err(Error::InvalidData(_)) => (),
+            other => panic!("expected InvalidData, got {:?}", other.map(|data| data.len())),
+        }
+        assert_eq!(1 << 20, Zip::new(Cursor::new(data)).unwrap().read_vec("bomb.txt".into()).unwrap().len());
+    }
+
+    #[test]
+    fn test_not_an_archive_fails_to_open() {
+        assert!(Zip::new(Cursor::new(b"hello world".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_higher_order_loader_overrides_archive() {
+        let res = Resources::new()
+            .loaded_from("data", 0, Zip::new(Cursor::new(zip(&[
+                ("a.txt", b"from zip", true),
+                ("b.txt", b"from zip", true),
+            ]))).unwrap())
+            .loaded_from("dev", 1, InMemory::new().with("b.txt", b"from dev"));
+
+        assert_eq!(b"from zip", &res.resource("a.txt").get().unwrap()[..]);
+        assert_eq!(b"from dev", &res.resource("b.txt").get().unwrap()[..]);
+
+        res.resource("a.txt").write(b"changed").unwrap();
+        assert_eq!(b"changed", &res.resource("a.txt").get().unwrap()[..]);
+    }
+
+    #[test]
+    fn test_writes_go_through_archive_to_lower_order_loader() {
+        let res = Resources::new()
+            .loaded_from("saves", 0, InMemory::new())
+            .loaded_from("data", 1, Zip::new(Cursor::new(zip(&[
+                ("a.txt", b"from zip", true),
+            ]))).unwrap());
+
+        res.resource("b.txt").write(b"saved").unwrap();
+        assert_eq!(b"saved", &res.resource("b.txt").get().unwrap()[..]);
+        assert_eq!("saves", res.resource("b.txt").metadata().unwrap().loader_id);
+
+        // the archive still serves its own resources, the write is hidden by it
+        res.resource("a.txt").write(b"changed").unwrap();
+        assert_eq!(b"from zip", &res.resource("a.txt").get().unwrap()[..]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Build script helper that embeds a directory into the executable.
+//!
+//! In `build.rs`, generate the list of files:
+//!
+//! ```ignore
+//! extern crate resources;
+//!
+//! fn main() {
+//!     let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//!     resources::embed::embed_dir("core", out_dir.join("core.rs")).unwrap();
+//! }
+//! ```
+//!
+//! Then include it as an `InMemory` backend (requires `backend_in_memory`):
+//!
+//! ```ignore
+//! let resources = Resources::new()
+//!     .loaded_from("core", 0, backend::InMemory::from_embedded(embedded_resources!("core.rs")));
+//! ```
+
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Includes the file generated by `embed_dir` in `OUT_DIR` as `&'static [(&'static str, &'static [u8])]`.
+#[macro_export]
+macro_rules! embedded_resources {
+    ($file:expr) => {
+        include!(concat!(env!("OUT_DIR"), "/", $file))
+    };
+}
+
+/// Writes the Rust expression that includes all files in `dir` to `output`, and tells cargo
+/// to run the build script again when these files or the directories change.
+///
+/// Resource paths are relative to `dir`.
+pub fn embed_dir<D: AsRef<Path>, O: AsRef<Path>>(dir: D, output: O) -> io::Result<()> {
+    let dir = fs::canonicalize(dir)?;
+
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    collect_files(&dir, &mut files, &mut dirs)?;
+    files.sort();
+
+    // directories are watched too, so that added files are embedded
+    for dir in &dirs {
+        println!("cargo:rerun-if-changed={}", dir.display());
+    }
+    for &(_, ref file) in &files {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+
+    fs::File::create(output)?.write_all(generate(&files).as_bytes())
+}
+
+/// Collects the files with their resource paths, and the visited directories starting with `dir`.
+fn collect_files(dir: &Path, files: &mut Vec<(String, PathBuf)>, dirs: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut pending = vec![(String::new(), dir.to_path_buf())];
+
+    while let Some((prefix, dir)) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().into_string()
+                .map_err(|name| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("file name {:?} is not valid UTF-8", name),
+                ))?;
+            let path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+
+            if entry.file_type()?.is_dir() {
+                pending.push((path, entry.path()));
+            } else {
+                files.push((path, entry.path()));
+            }
+        }
+        dirs.push(dir);
+    }
+
+    Ok(())
+}
+
+fn generate(files: &[(String, PathBuf)]) -> String {
+    let mut result = String::from("&[\n");
+    for &(ref path, ref file) in files {
+        let _ = writeln!(
+            &mut result,
+            "    ({:?}, &include_bytes!({:?})[..]),",
+            path,
+            file.to_string_lossy()
+        );
+    }
+    result.push_str("]\n");
+    result
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tempdir;
+
+    use self::tempdir::TempDir;
+    use super::{embed_dir, collect_files};
+    use backend::{Backend, InMemory};
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_all_files_are_included_with_relative_paths() {
+        let dir = TempDir::new("embed").unwrap();
+        let root = dir.path().join("assets");
+        fs::create_dir_all(root.join("shaders")).unwrap();
+        fs::File::create(root.join("Config.toml")).unwrap().write_all(b"a").unwrap();
+        fs::File::create(root.join("shaders").join("shiny.vert")).unwrap().write_all(b"b").unwrap();
+
+        let output = dir.path().join("assets.rs");
+        embed_dir(&root, &output).unwrap();
+
+        let root = fs::canonicalize(&root).unwrap();
+        let expected = format!(
+            "&[\n    (\"Config.toml\", &include_bytes!({:?})[..]),\n    (\"shaders/shiny.vert\", &include_bytes!({:?})[..]),\n]\n",
+            root.join("Config.toml").to_string_lossy(),
+            root.join("shaders").join("shiny.vert").to_string_lossy(),
+        );
+        assert_eq!(expected, fs::read_to_string(&output).unwrap());
+    }
+
+    #[test]
+    fn test_nested_directories_are_collected() {
+        let dir = TempDir::new("embed").unwrap();
+        fs::create_dir_all(dir.path().join("shaders").join("render_gl")).unwrap();
+        fs::File::create(dir.path().join("shaders").join("render_gl").join("lines.vert")).unwrap();
+
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        collect_files(dir.path(), &mut files, &mut dirs).unwrap();
+        dirs.sort();
+
+        assert_eq!(1, files.len());
+        assert_eq!(
+            vec![
+                dir.path().to_path_buf(),
+                dir.path().join("shaders"),
+                dir.path().join("shaders").join("render_gl"),
+            ],
+            dirs
+        );
+    }
+
+    #[test]
+    fn test_embedded_files_are_loaded_into_memory() {
+        let be = InMemory::from_embedded(&[("Config.toml", b"a"), ("shaders/shiny.vert", b"b")]);
+
+        assert_eq!(b"a", &be.read_vec("Config.toml".into()).unwrap()[..]);
+        assert_eq!(b"b", &be.read_vec("shaders/shiny.vert".into()).unwrap()[..]);
+    }
+}
@@ -1,17 +1,124 @@
+use std::fmt;
 use std::io;
-use ResourcePathBuf;
-use failure;
+use {ResourcePath, ResourcePathBuf};
+use failure::{self, Fail};
 
-#[derive(Debug, Fail)]
+#[derive(Debug)]
 pub enum Error {
-    #[fail(display = "I/O error")]
-    Io(#[cause] io::Error),
-    #[fail(display = "Item not found")]
+    /// The backend failed to read or write the underlying storage.
+    Io(io::Error),
     NotFound,
-    #[fail(display = "Backend can not write")]
     NotWritable,
-    #[fail(display = "Failed to write {}, {}", path, inner)]
-    BackendFailedToWrite { path: ResourcePathBuf, inner: failure::Error },
+    PathOutsideRoot { path: ResourcePathBuf },
+    HashMismatch { path: ResourcePathBuf, expected: u64, actual: u64 },
+    /// The storage was read, but its format is invalid, for example, a corrupted archive index.
+    InvalidData(String),
+    /// Contents were read, but could not be decompressed or decoded into a value.
+    FailedToDecode { path: ResourcePathBuf, inner: failure::Error },
+    /// Error of a `Resources` operation, with the path and the loader that failed.
+    Resource { context: ErrorContext, inner: Box<Error> },
+}
+
+impl Error {
+    pub(crate) fn in_context(self, operation: Operation, path: &ResourcePath, loader_id: Option<&str>) -> Error {
+        Error::Resource {
+            context: ErrorContext {
+                operation,
+                path: path.into(),
+                loader_id: loader_id.map(String::from),
+            },
+            inner: Box::new(self),
+        }
+    }
+
+    /// Returns the operation, path and loader if the error was returned by `Resources`.
+    pub fn resource_context(&self) -> Option<&ErrorContext> {
+        match *self {
+            Error::Resource { ref context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the context added by `Resources`.
+    pub fn root(&self) -> &Error {
+        match *self {
+            Error::Resource { ref inner, .. } => inner.root(),
+            ref other => other,
+        }
+    }
+
+    /// Same as `root`, but takes the ownership of the error.
+    pub fn into_root(self) -> Error {
+        match self {
+            Error::Resource { inner, .. } => inner.into_root(),
+            other => other,
+        }
+    }
+}
+
+/// Kind of the `Resources` operation that failed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Read,
+    Write,
+    Delete,
+    Rename,
+    ReadMetadata,
+}
+
+/// Operation, resource path and loader of the failed `Resources` operation.
+///
+/// Displayed like "reading shaders/shiny.vert from loader core".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ErrorContext {
+    pub operation: Operation,
+    pub path: ResourcePathBuf,
+    /// Id of the loader that failed, or `None` if no loader could serve the resource.
+    pub loader_id: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let (verb, preposition) = match self.operation {
+            Operation::Read => ("reading", "from"),
+            Operation::Write => ("writing", "to"),
+            Operation::Delete => ("deleting", "from"),
+            Operation::Rename => ("renaming", "in"),
+            Operation::ReadMetadata => ("reading metadata of", "from"),
+        };
+        write!(f, "{} {}", verb, self.path)?;
+        if let Some(ref loader_id) = self.loader_id {
+            write!(f, " {} loader {}", preposition, loader_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Error::Io(_) => write!(f, "I/O error"),
+            Error::NotFound => write!(f, "Item not found"),
+            Error::NotWritable => write!(f, "Backend can not write"),
+            Error::PathOutsideRoot { ref path } => write!(f, "Path {} leads outside of the root", path),
+            Error::HashMismatch { ref path, expected, actual } =>
+                write!(f, "Contents of {} do not match the expected hash {:016x}, got {:016x}", path, expected, actual),
+            Error::InvalidData(ref message) => write!(f, "Invalid data: {}", message),
+            Error::FailedToDecode { ref path, .. } => write!(f, "Failed to decode {}", path),
+            Error::Resource { ref context, .. } => write!(f, "Failed {}", context),
+        }
+    }
+}
+
+impl Fail for Error {
+    fn cause(&self) -> Option<&Fail> {
+        match *self {
+            Error::Io(ref inner) => Some(inner),
+            Error::FailedToDecode { ref inner, .. } => Some(inner.as_fail()),
+            Error::Resource { ref inner, .. } => Some(&**inner),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -23,10 +130,51 @@ impl From<io::Error> for Error {
 impl ::std::cmp::PartialEq for Error {
     fn eq(&self, other: &Error) -> bool {
         match (self, other) {
-            (Error::Io(_), Error::Io(_)) => true,
-            (Error::NotFound, Error::NotFound) => true,
-            (a, b) if a == b => true,
+            (&Error::Io(ref a), &Error::Io(ref b)) => a.kind() == b.kind(),
+            (&Error::NotFound, &Error::NotFound) => true,
+            (&Error::NotWritable, &Error::NotWritable) => true,
+            (&Error::PathOutsideRoot { path: ref a }, &Error::PathOutsideRoot { path: ref b }) => a == b,
+            (
+                &Error::HashMismatch { path: ref a, expected: a_expected, actual: a_actual },
+                &Error::HashMismatch { path: ref b, expected: b_expected, actual: b_actual },
+            ) => a == b && a_expected == b_expected && a_actual == b_actual,
+            (&Error::InvalidData(ref a), &Error::InvalidData(ref b)) => a == b,
+            (&Error::FailedToDecode { path: ref a, .. }, &Error::FailedToDecode { path: ref b, .. }) => a == b,
+            (
+                &Error::Resource { context: ref a, inner: ref a_inner },
+                &Error::Resource { context: ref b, inner: ref b_inner },
+            ) => a == b && a_inner == b_inner,
             _ => false,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Error, Operation};
+    use failure::Fail;
+
+    #[test]
+    fn errors_should_be_compared_by_value() {
+        let outside = || Error::PathOutsideRoot { path: String::from("../a").into() };
+
+        assert_eq!(outside(), outside());
+        assert_eq!(Error::NotWritable, Error::NotWritable);
+        assert_ne!(Error::NotWritable, Error::NotFound);
+        assert_ne!(outside(), Error::PathOutsideRoot { path: String::from("../b").into() });
+    }
+
+    #[test]
+    fn context_should_be_displayed_with_the_loader_and_kept_out_of_the_root() {
+        let error = Error::NotFound.in_context(Operation::Read, "shaders/shiny.vert".into(), Some("core"));
+
+        assert_eq!("Failed reading shaders/shiny.vert from loader core", error.to_string());
+        assert_eq!("Item not found", error.cause().unwrap().to_string());
+        assert_eq!(&Error::NotFound, error.root());
+        assert_eq!(Some("core"), error.resource_context().and_then(|c| c.loader_id.as_ref()).map(|id| &id[..]));
+
+        let error = Error::NotWritable.in_context(Operation::Write, "a".into(), None);
+        assert_eq!("Failed writing a", error.to_string());
+        assert_eq!(Error::NotWritable, error.into_root());
+    }
+}
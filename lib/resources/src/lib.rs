@@ -9,73 +9,275 @@ pub use self::path::{ResourcePath, ResourcePathBuf};
 
 mod shared;
 
-use self::shared::{SharedResources, UserKey, InternalSyncPoint};
+use self::shared::{resource_key, SharedResources, Mounts, ResourceBackends, ServingBackend, UserKey, InternalSyncPoint};
+pub use self::shared::{ChangeKind, WritePolicy, AccessStats, AccessCounts};
 
 pub mod backend;
 
+#[macro_use]
+pub mod embed;
+
 mod error;
-pub use self::error::Error;
+pub use self::error::{Error, ErrorContext, Operation};
+
+mod asset;
+pub use self::asset::{Asset, FromResource};
+
+mod read_pool;
+pub use self::read_pool::PendingRead;
+use self::read_pool::ReadPool;
 
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
+use std::io;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::sync::Mutex;
+use std::sync::mpsc;
 
 pub struct SyncPoint(InternalSyncPoint);
 
+/// Information about the resource returned by `Resource::metadata`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    pub len: u64,
+    /// Time of the last modification, if the backend keeps it.
+    pub modified: Option<SystemTime>,
+    /// Id of the loader that serves the resource.
+    pub loader_id: String,
+}
+
 #[derive(Clone)]
 pub struct Resources {
     shared: Arc<RwLock<SharedResources>>,
+    assets: Arc<Mutex<asset::AssetCache>>,
+    read_pool: Arc<ReadPool>,
 }
 
 impl Resources {
     pub fn new() -> Resources {
         Resources {
             shared: Arc::new(RwLock::new(SharedResources::new())),
+            assets: Arc::new(Mutex::new(asset::AssetCache::new())),
+            read_pool: Arc::new(ReadPool::new(read_pool::DEFAULT_READ_WORKERS)),
         }
     }
 
+    /// Keep up to `bytes` of resource contents in memory, dropping the least recently used.
+    ///
+    /// The cache is used by `Resource::get` and `Resource::get_shared`, and is disabled by default.
+    pub fn with_cache_budget(self, bytes: usize) -> Resources {
+        self.shared.write()
+            .expect("failed to lock for write")
+            .set_cache_budget(bytes);
+        self
+    }
+
+    /// Record reads, read bytes and time per path and per loader, and the use of the cache.
+    ///
+    /// Statistics are disabled by default, because every read then also locks the recorder.
+    pub fn with_access_stats(self) -> Resources {
+        self.shared.write()
+            .expect("failed to lock for write")
+            .enable_stats();
+        self
+    }
+
+    /// Returns statistics recorded since `with_access_stats` or the last reset, `None` if disabled.
+    pub fn access_stats(&self) -> Option<AccessStats> {
+        self.shared.read()
+            .expect("failed to lock for read")
+            .stats()
+            .map(|stats| stats.snapshot())
+    }
+
+    /// Writes recorded statistics as a table, or nothing if they are disabled.
+    pub fn dump_access_stats(&self, output: &mut io::Write) -> io::Result<()> {
+        match self.access_stats() {
+            Some(stats) => write!(output, "{}", stats),
+            None => Ok(()),
+        }
+    }
+
+    pub fn reset_access_stats(&self) {
+        if let Some(stats) = self.shared.read().expect("failed to lock for read").stats() {
+            stats.reset();
+        }
+    }
+
+    /// Set the number of threads that run `Resource::get_async` reads.
+    ///
+    /// Resource proxies that already exist keep using the previous threads.
+    pub fn with_read_workers(mut self, count: usize) -> Resources {
+        self.read_pool = Arc::new(ReadPool::new(count));
+        self
+    }
+
     pub fn loaded_from<L: backend::Backend + 'static>(self, loader_id: &str, order: isize, backend: L) -> Resources {
         self.insert_loader(loader_id, order, backend);
         self
     }
 
+    /// Mounts the backend under the path, the resources of this loader are available at
+    /// `mount_path/backend_path`.
+    ///
+    /// Panics if the mount path leads outside of the root.
+    pub fn mounted_from<P: AsRef<ResourcePath>, L: backend::Backend + 'static>(self, loader_id: &str, order: isize, mount_path: P, backend: L) -> Resources {
+        self.insert_mounted_loader(loader_id, order, mount_path, backend);
+        self
+    }
+
     pub fn insert_loader<L: backend::Backend + 'static>(&self, loader_id: &str, order: isize, backend: L) {
+        self.insert_mounted_loader(loader_id, order, "", backend);
+    }
+
+    pub fn insert_mounted_loader<P: AsRef<ResourcePath>, L: backend::Backend + 'static>(&self, loader_id: &str, order: isize, mount_path: P, backend: L) {
+        let loader = mounts(&self.shared).new_loader(loader_id, order, mount_path.as_ref(), backend);
+        self.shared.write()
+            .expect("failed to lock for write")
+            .insert_loader(loader);
+    }
+
+    /// Set the way the loader is modified, loaders are `WritePolicy::WriteTarget` by default,
+    /// or `WritePolicy::ReadOnly` if the backend can not write when the loader is inserted.
+    pub fn with_write_policy(self, loader_id: &str, write_policy: WritePolicy) -> Resources {
+        self.set_write_policy(loader_id, write_policy);
+        self
+    }
+
+    pub fn set_write_policy(&self, loader_id: &str, write_policy: WritePolicy) {
         let mut resources = self.shared.write()
             .expect("failed to lock for write");
-        resources.insert_loader(loader_id, order, backend);
+        resources.set_write_policy(loader_id, write_policy);
     }
 
     pub fn remove_loader(&self, loader_id: &str) {
-        let mut resources = self.shared.write()
-            .expect("failed to lock for write");
-        resources.remove_loader(loader_id);
+        let outdated_at = Instant::now();
+        let (removed, resource_paths) = {
+            let mut resources = self.shared.write()
+                .expect("failed to lock for write");
+            (resources.remove_loader(loader_id, outdated_at), resources.resource_paths())
+        };
+        let changes = removed.removal_changes(&mounts(&self.shared), &resource_paths);
+        self.shared.write()
+            .expect("failed to lock for write")
+            .notify_changes(changes, outdated_at);
     }
 
     pub fn resource<P: AsRef<ResourcePath>>(&self, path: P) -> Resource {
         Resource {
             shared: self.shared.clone(),
+            read_pool: self.read_pool.clone(),
             key: self.shared.write()
                 .expect("failed to lock for write")
                 .new_resource_user(path),
         }
     }
 
-    pub fn new_changes(&self) -> Option<SyncPoint> {
+    /// List paths of all resources in the directory and its subdirectories.
+    ///
+    /// The paths from all loaders are merged, sorted and contain no duplicates.
+    pub fn list<P: AsRef<ResourcePath>>(&self, dir: P) -> Vec<ResourcePathBuf> {
+        mounts(&self.shared).list(dir.as_ref())
+    }
+
+    /// Move the resource in the loader that has highest order number and contains it.
+    ///
+    /// If the new path is outside of the mount path of that loader, the contents are written to the
+    /// new path like with `Resource::write`, and the old resource is deleted.
+    pub fn rename<F: AsRef<ResourcePath>, T: AsRef<ResourcePath>>(&self, from: F, to: T) -> Result<(), Error> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        for path in &[from, to] {
+            resource_key(path).map_err(|e| e.in_context(Operation::Rename, from, None))?;
+        }
+        let serving = mounts(&self.shared)
+            .serving_backend(from)
+            .ok_or_else(|| Error::NotFound.in_context(Operation::Rename, from, None))?;
+        let in_context = |e: Error| e.in_context(Operation::Rename, from, Some(&serving.loader_id));
+        if serving.write_policy == WritePolicy::ReadOnly {
+            return Err(in_context(Error::NotWritable));
+        }
+
+        match to.strip_prefix(&serving.mount_path) {
+            Some(backend_to) => {
+                serving.backend.write()
+                    .expect("failed to lock backend for write")
+                    .rename(&serving.path, backend_to)
+                    .map_err(&in_context)?;
+                let kind = mounts(&self.shared).change_after_removal(to);
+                self.shared.write()
+                    .expect("failed to lock for write")
+                    .notify_path_changed(to, kind, Instant::now());
+            },
+            None => {
+                let data = serving.backend.read()
+                    .expect("failed to lock backend for read")
+                    .read_vec(&serving.path)
+                    .map_err(&in_context)?;
+                self.resource(to).write(&data).map_err(&in_context)?;
+                serving.backend.write()
+                    .expect("failed to lock backend for write")
+                    .delete(&serving.path)
+                    .map_err(&in_context)?;
+            },
+        }
+
+        let kind = mounts(&self.shared).change_after_removal(from);
         self.shared.write()
             .expect("failed to lock for write")
-            .new_changes()
+            .notify_path_changed(from, kind, Instant::now());
+        Ok(())
+    }
+
+    /// Load a value decoded from the resource.
+    ///
+    /// The decoded value is shared with other handles of the same path and type.
+    pub fn load<T: FromResource, P: AsRef<ResourcePath>>(&self, path: P) -> Result<Asset<T>, Error> {
+        asset::load(self, &self.assets, path.as_ref())
+    }
+
+    /// Returns a channel that receives paths of changed resources.
+    ///
+    /// Changes are sent on writes, when loaders are inserted or removed, and, for changes made
+    /// outside of this process, when they are picked up by `new_changes`. Resources hidden by
+    /// a loader with a higher order produce no changes.
+    pub fn subscribe(&self) -> mpsc::Receiver<(ResourcePathBuf, ChangeKind)> {
+        self.shared.read()
+            .expect("failed to lock for read")
+            .subscribe()
+    }
+
+    pub fn new_changes(&self) -> Option<SyncPoint> {
+        let outdated_at = Instant::now();
+        let changes = mounts(&self.shared).take_modified_paths();
+        let mut resources = self.shared.write()
+            .expect("failed to lock for write");
+        resources.notify_changes(changes, outdated_at);
+        resources.new_changes()
             .map(|p| SyncPoint(p))
     }
 
     pub fn notify_changes_synced(&self, sync_point: SyncPoint) {
-        self.shared.write()
+        let backends = self.shared.write()
             .expect("failed to lock for write")
-            .notify_changes_synced(sync_point.0)
+            .notify_changes_synced(sync_point.0);
+        for (backend, point) in backends {
+            backend.write()
+                .expect("failed to lock backend for write")
+                .notify_changes_synced(point);
+        }
     }
 }
 
+/// Returns the loaders, so that their backends can be locked without locking `SharedResources`.
+fn mounts(shared: &RwLock<SharedResources>) -> Mounts {
+    shared.read()
+        .expect("failed to lock for read")
+        .mounts()
+}
+
 pub struct Resource {
     shared: Arc<RwLock<SharedResources>>,
+    read_pool: Arc<ReadPool>,
     key: UserKey,
 }
 
@@ -85,72 +287,268 @@ impl Resource {
     /// This unreliable command can tell if at least one backend can return the resource at this moment.
     /// Not that the next moment the resource can be gone.
     pub fn exists(&self) -> bool {
-        let shared_ref = &self.shared;
-        let resources = shared_ref.read()
-            .expect("failed to lock for read");
-
-        resources
-            .get_resource_path_backend_containing_resource(self.key)
-            .map(|(path, _, b)| b.exists(path))
-            .unwrap_or(false)
+        let path = self.path();
+        mounts(&self.shared)
+            .serving_backend(&path)
+            .is_some()
     }
 
     /// Read value from the backend that has highest order number and contains the resource.
+    ///
+    /// The backend is read without locking other resources, so reads of different resources
+    /// can run in parallel.
     pub fn get(&self) -> Result<Vec<u8>, Error> {
-        let shared_ref = &self.shared;
-        let mut resources = shared_ref.write()
-            .expect("failed to lock for write");
+        let backends = self.backends(Operation::Read)?;
+        if let Some(data) = self.cached() {
+            self.notify_did_read(backends.modification_time);
+            return Ok(data.to_vec());
+        }
 
-        let mut did_read = None;
-
-        {
-            for (path, modification_time, backend) in resources.resource_backends(self.key) {
-                match backend.read_vec(path) {
-                    Ok(result) => {
-                        did_read = Some((modification_time, result));
-                        break;
-                    },
-                    Err(Error::NotFound) => continue,
-                    Err(e) => return Err(e),
-                }
-            }
+        let result = read_from_backends(&backends)?;
+        self.insert_cached(backends.version, result.len(), || Arc::from(&result[..]));
+        self.notify_did_read(backends.modification_time);
+        Ok(result)
+    }
+
+    /// Same as `get`, but returns the contents stored in the `Resources` cache without copying.
+    pub fn get_shared(&self) -> Result<Arc<[u8]>, Error> {
+        let backends = self.backends(Operation::Read)?;
+        if let Some(data) = self.cached() {
+            self.notify_did_read(backends.modification_time);
+            return Ok(data);
         }
 
-        if let Some((modification_time, result)) = did_read {
-            resources.notify_did_read(self.key, modification_time);
-            return Ok(result);
+        let result: Arc<[u8]> = read_from_backends(&backends)?.into();
+        self.insert_cached(backends.version, result.len(), || result.clone());
+        self.notify_did_read(backends.modification_time);
+        Ok(result)
+    }
+
+    /// Open the resource from the backend that has highest order number and contains the resource.
+    ///
+    /// Contents are read on demand if the backend supports it, otherwise they are read into memory.
+    /// The resource is marked as read.
+    pub fn open(&self) -> Result<Box<backend::Stream>, Error> {
+        let backends = self.backends(Operation::Read)?;
+        let stream = first_from_backends(&backends, |backend, path| backend.open(path))?;
+        self.notify_did_read(backends.modification_time);
+        Ok(stream)
+    }
+
+    /// Read up to `len` bytes starting at `offset`, less if the resource ends earlier.
+    ///
+    /// Unlike other reads, this does not mark the resource as read.
+    pub fn read_range(&self, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+        first_from_backends(&self.backends(Operation::Read)?, |backend, path| backend.read_range(path, offset, len))
+    }
+
+    /// Returns `backend::content_hash` of the contents, which stays the same across runs.
+    ///
+    /// Contents are hashed without copying them into memory if they are not cached.
+    /// Like `read_range`, this does not mark the resource as read.
+    pub fn hash(&self) -> Result<u64, Error> {
+        if let Some(data) = self.cached() {
+            return Ok(backend::content_hash(&data));
         }
 
-        Err(Error::NotFound)
+        first_from_backends(&self.backends(Operation::Read)?, |backend, path| {
+            let mut hasher = backend::ContentHasher::new();
+            backend.read_into(path, &mut hasher)?;
+            Ok(hasher)
+        })
+            .map(|hasher| hasher.finish())
     }
 
-    /// Write value to the backend that has highest order number and can write.
-    pub fn write(&self, data: &[u8]) -> Result<(), Error> {
-        let shared_ref = &self.shared;
-        let mut resources = shared_ref.write()
-            .expect("failed to lock for write");
+    fn cached(&self) -> Option<Arc<[u8]>> {
+        self.shared.read()
+            .expect("failed to lock for read")
+            .cached(self.key)
+    }
 
-        let mut did_write = false;
-
-        {
-            for (path, _, backend) in resources.resource_backends(self.key) {
-                match backend.write(path, data) {
-                    Ok(()) => {
-                        did_write = true;
-                        break;
-                    },
-                    Err(Error::NotWritable) => continue,
-                    Err(e) => return Err(e),
-                }
+    fn insert_cached<F: FnOnce() -> Arc<[u8]>>(&self, version: u64, len: usize, data: F) {
+        self.shared.read()
+            .expect("failed to lock for read")
+            .insert_cached(self.key, version, len, data);
+    }
+
+    /// Read value on a thread of the `Resources` read pool.
+    ///
+    /// The resource is marked as read when the result is received from `PendingRead`.
+    pub fn get_async(&self) -> PendingRead {
+        let (sender, receiver) = mpsc::channel();
+        let backends = match self.backends(Operation::Read) {
+            Ok(backends) => backends,
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return PendingRead::new(self, None, receiver);
+            },
+        };
+        let outdated_at = backends.modification_time;
+
+        let job_sender = sender.clone();
+        let executed = self.read_pool.execute(move || {
+            let _ = job_sender.send(read_from_backends(&backends));
+        });
+        if let Err(e) = executed {
+            let _ = sender.send(Err(e.in_context(Operation::Read, &self.path(), None)));
+        }
+
+        PendingRead::new(self, outdated_at, receiver)
+    }
+
+    fn backends(&self, operation: Operation) -> Result<ResourceBackends, Error> {
+        self.check_path(operation)?;
+        Ok(self.shared.read()
+            .expect("failed to lock for read")
+            .resource_backends(self.key))
+    }
+
+    fn notify_did_read(&self, modification_time: Option<Instant>) {
+        self.notify_user_did_read(self.key, modification_time);
+    }
+
+    /// Marks another user of the same resource as read, used by `PendingRead`.
+    fn notify_user_did_read(&self, key: UserKey, modification_time: Option<Instant>) {
+        self.shared.write()
+            .expect("failed to lock for write")
+            .notify_did_read(key, modification_time);
+    }
+
+    /// Delete the resource from the backend that has highest order number and contains it.
+    ///
+    /// The resource is still available if a backend with a lower order contains it.
+    pub fn delete(&self) -> Result<(), Error> {
+        let (path, serving) = self.serving_backend(Operation::Delete)?;
+        let in_context = |e: Error| e.in_context(Operation::Delete, &path, Some(&serving.loader_id));
+        if serving.write_policy == WritePolicy::ReadOnly {
+            return Err(in_context(Error::NotWritable));
+        }
+        serving.backend.write()
+            .expect("failed to lock backend for write")
+            .delete(&serving.path)
+            .map_err(&in_context)?;
+
+        let kind = mounts(&self.shared).change_after_removal(&path);
+        self.shared.write()
+            .expect("failed to lock for write")
+            .notify_path_changed(&path, kind, Instant::now());
+        Ok(())
+    }
+
+    /// Returns the size, modification time and loader of the resource.
+    pub fn metadata(&self) -> Result<Metadata, Error> {
+        let (path, serving) = self.serving_backend(Operation::ReadMetadata)?;
+        let metadata = serving.backend.read()
+            .expect("failed to lock backend for read")
+            .metadata(&serving.path)
+            .map_err(|e| e.in_context(Operation::ReadMetadata, &path, Some(&serving.loader_id)))?;
+
+        Ok(Metadata {
+            len: metadata.len,
+            modified: metadata.modified,
+            loader_id: serving.loader_id,
+        })
+    }
+
+    /// Id of the loader that currently serves the resource.
+    pub(crate) fn serving_loader_id(&self) -> Option<String> {
+        self.serving_backend(Operation::Read).ok().map(|(_, serving)| serving.loader_id)
+    }
+
+    fn serving_backend(&self, operation: Operation) -> Result<(ResourcePathBuf, ServingBackend), Error> {
+        let path = self.check_path(operation)?;
+        let serving = mounts(&self.shared)
+            .serving_backend(&path)
+            .ok_or_else(|| Error::NotFound.in_context(operation, &path, None))?;
+
+        Ok((path, serving))
+    }
+
+    fn path(&self) -> ResourcePathBuf {
+        self.shared.read()
+            .expect("failed to lock for read")
+            .resource_path(self.key)
+            .map(ResourcePathBuf::from)
+            .unwrap_or_else(|| ResourcePathBuf::from(String::new()))
+    }
+
+    /// Returns the path, or `PathOutsideRoot` if it leads outside of the root.
+    ///
+    /// Done before the backends are looked up, so that no backend receives such path.
+    fn check_path(&self, operation: Operation) -> Result<ResourcePathBuf, Error> {
+        let path = self.path();
+        resource_key(&path).map_err(|e| e.in_context(operation, &path, None))
+    }
+
+    /// Write value to the loader that has highest order number and accepts the write according
+    /// to its `WritePolicy`.
+    ///
+    /// Fails with `NotWritable` if that loader's backend can not write, without trying loaders
+    /// with a lower order. Such a loader only accepts writes if its policy was set explicitly.
+    pub fn write(&self, data: &[u8]) -> Result<(), Error> {
+        let mut written_loader = None;
+        let backends = self.backends(Operation::Write)?;
+
+        for serving in &backends.backends {
+            let mut backend = serving.backend.write()
+                .expect("failed to lock backend for write");
+            match serving.write_policy {
+                WritePolicy::ReadOnly => continue,
+                WritePolicy::WriteExisting if !backend.exists(&serving.path) => continue,
+                _ => (),
             }
+            backend.write(&serving.path, data)
+                .map_err(|e| e.in_context(Operation::Write, &backends.path, Some(&serving.loader_id)))?;
+            written_loader = Some(&serving.loader_id);
+            break;
         }
 
-        if did_write {
-            resources.notify_did_write(self.key, Instant::now());
+        if let Some(loader_id) = written_loader {
+            self.notify_did_write(loader_id, &backends.path);
             return Ok(());
         }
 
-        Err(Error::NotWritable)
+        Err(Error::NotWritable.in_context(Operation::Write, &backends.path, None))
+    }
+
+    /// Write value to the loader with the id, regardless of loader order.
+    ///
+    /// Fails with `NotWritable` if the loader is read-only, and with `NotFound` if there is no such
+    /// loader or the resource is outside of its mount path.
+    pub fn write_to(&self, loader_id: &str, data: &[u8]) -> Result<(), Error> {
+        let resource_path = self.check_path(Operation::Write)?;
+        let in_context = |e: Error| e.in_context(Operation::Write, &resource_path, Some(loader_id));
+        let (path, write_policy, backend) = self.shared.read()
+            .expect("failed to lock for read")
+            .get_resource_path_backend(loader_id, self.key)
+            .map(|(path, _, write_policy, backend)| (ResourcePathBuf::from(path), write_policy, backend))
+            .ok_or_else(|| in_context(Error::NotFound))?;
+
+        if write_policy == WritePolicy::ReadOnly {
+            return Err(in_context(Error::NotWritable));
+        }
+
+        backend.write()
+            .expect("failed to lock backend for write")
+            .write(&path, data)
+            .map_err(&in_context)?;
+
+        self.notify_did_write(loader_id, &resource_path);
+        Ok(())
+    }
+
+    /// Outdates other proxies and sends the change to subscribers, unless the written loader
+    /// is hidden by a loader with a higher order, which means the served contents did not change.
+    fn notify_did_write(&self, loader_id: &str, path: &ResourcePath) {
+        let is_served = mounts(&self.shared)
+            .serving_backend(path)
+            .map(|serving| serving.loader_id == loader_id)
+            .unwrap_or(false);
+        if is_served {
+            self.shared.write()
+                .expect("failed to lock for write")
+                .notify_did_write(self.key, Instant::now());
+        }
     }
 
     pub fn is_modified(&self) -> bool {
@@ -172,6 +570,7 @@ impl Clone for Resource {
 
         Resource {
             shared: self.shared.clone(),
+            read_pool: self.read_pool.clone(),
             key: new_key,
         }
     }
@@ -185,9 +584,111 @@ impl Drop for Resource {
     }
 }
 
+fn read_from_backends(resources: &ResourceBackends) -> Result<Vec<u8>, Error> {
+    first_from_backends(resources, |backend, path| backend.read_vec(path))
+}
+
+/// Returns the result of the first backend that does not return `NotFound`.
+///
+/// Errors are returned with the resource path and the loader that failed.
+fn first_from_backends<T, F>(resources: &ResourceBackends, read: F) -> Result<T, Error>
+    where T: ReadLen, F: Fn(&backend::Backend, &ResourcePath) -> Result<T, Error>
+{
+    for serving in &resources.backends {
+        let backend = serving.backend.read()
+            .expect("failed to lock backend for read");
+        let started = Instant::now();
+        match read(&**backend, &serving.path) {
+            Ok(result) => {
+                if let Some(ref stats) = resources.stats {
+                    stats.record_read(&resources.path, &serving.loader_id, result.read_len(), started.elapsed());
+                }
+                return Ok(result);
+            },
+            Err(Error::NotFound) => continue,
+            Err(e) => return Err(e.in_context(Operation::Read, &resources.path, Some(&serving.loader_id))),
+        }
+    }
+
+    Err(Error::NotFound.in_context(Operation::Read, &resources.path, None))
+}
+
+/// Number of bytes read from the backend, counted in `AccessStats`.
+trait ReadLen {
+    fn read_len(&self) -> u64;
+}
+
+impl ReadLen for Vec<u8> {
+    fn read_len(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl ReadLen for Box<backend::Stream> {
+    /// Streams are read on demand, after the backend is released.
+    fn read_len(&self) -> u64 {
+        0
+    }
+}
+
+impl ReadLen for backend::ContentHasher {
+    fn read_len(&self) -> u64 {
+        self.len()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::io;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Backend that blocks reads until the test releases them.
+    struct Blocking {
+        entered: Mutex<mpsc::Sender<()>>,
+        release: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl backend::Backend for Blocking {
+        fn can_write(&self) -> bool { false }
+        fn exists(&self, _path: &ResourcePath) -> bool { true }
+        fn list(&self, _dir: &ResourcePath) -> Vec<ResourcePathBuf> { Vec::new() }
+        fn notify_changes_synced(&mut self, _point: backend::BackendSyncPoint) {}
+        fn new_changes(&mut self) -> Option<backend::BackendSyncPoint> { None }
+
+        fn read_into(&self, _path: &ResourcePath, output: &mut io::Write) -> Result<(), Error> {
+            self.entered.lock().unwrap().send(()).unwrap();
+            self.release.lock().unwrap()
+                .recv_timeout(Duration::from_secs(10))
+                .expect("blocked read was not released");
+            output.write_all(b"slow")?;
+            Ok(())
+        }
+
+        fn write_from(&mut self, _path: &ResourcePath, _buffer: &mut io::Read) -> Result<(), Error> {
+            Err(Error::NotWritable)
+        }
+    }
+
+    /// Backend that has no resources and can not write.
+    struct Unwritable;
+
+    impl backend::Backend for Unwritable {
+        fn can_write(&self) -> bool { false }
+        fn exists(&self, _path: &ResourcePath) -> bool { false }
+        fn list(&self, _dir: &ResourcePath) -> Vec<ResourcePathBuf> { Vec::new() }
+        fn notify_changes_synced(&mut self, _point: backend::BackendSyncPoint) {}
+        fn new_changes(&mut self) -> Option<backend::BackendSyncPoint> { None }
+
+        fn read_into(&self, _path: &ResourcePath, _output: &mut io::Write) -> Result<(), Error> {
+            Err(Error::NotFound)
+        }
+
+        fn write_from(&mut self, _path: &ResourcePath, _buffer: &mut io::Read) -> Result<(), Error> {
+            Err(Error::NotWritable)
+        }
+    }
 
     #[test]
     fn with_no_loaders_should_have_no_reader() {
@@ -213,6 +714,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn paths_outside_of_the_root_should_be_rejected_before_reaching_backends() {
+        let res = Resources::new()
+            .loaded_from("a", 0, backend::InMemory::new());
+        let outside = res.resource("a/../../x");
+
+        match outside.write(b"hello").map_err(Error::into_root) {
+            Err(Error::PathOutsideRoot { .. }) => (),
+            other => panic!("expected PathOutsideRoot, got {:?}", other),
+        }
+        match outside.get().map_err(Error::into_root) {
+            Err(Error::PathOutsideRoot { .. }) => (),
+            other => panic!("expected PathOutsideRoot, got {:?}", other),
+        }
+        match res.rename("x", "../x").map_err(Error::into_root) {
+            Err(Error::PathOutsideRoot { .. }) => (),
+            other => panic!("expected PathOutsideRoot, got {:?}", other),
+        }
+        assert!(!outside.exists());
+        assert!(res.list("").is_empty());
+    }
+
     #[test]
     fn there_should_be_no_changes_and_resources_should_not_be_modified_at_start() {
         let res = Resources::new()
@@ -335,6 +858,688 @@ mod test {
         assert!(res.new_changes().is_none());
     }
 
+    #[test]
+    fn async_read_should_deliver_value_and_mark_resource_read() {
+        let res = Resources::new()
+            .with_read_workers(3)
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("a", b"hello")
+                    .with("b", b"world"),
+            );
+
+        let a = res.resource("a");
+        let b = res.resource("b");
+        res.resource("a").write(b"hello again").unwrap();
+        assert!(a.is_modified());
+
+        let mut pending_a = a.get_async();
+        let pending_b = b.get_async();
+
+        assert_eq!(&pending_b.wait().unwrap(), b"world");
+
+        let result = loop {
+            if let Some(result) = pending_a.poll() {
+                break result;
+            }
+            ::std::thread::yield_now();
+        };
+        assert_eq!(&result.unwrap(), b"hello again");
+        assert!(!a.is_modified());
+        assert!(pending_a.poll().is_none());
+    }
+
+    #[test]
+    fn async_read_should_be_waited_on_another_thread_after_resource_is_dropped() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("a", b"hello"),
+            );
+
+        let pending = res.resource("a").get_async();
+        let waiting = thread::spawn(move || pending.wait());
+
+        assert_eq!(&waiting.join().unwrap().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn async_read_should_deliver_errors() {
+        let res = Resources::new();
+        let missing = res.resource("missing");
+
+        assert_eq!(missing.get_async().wait().map_err(Error::into_root), Err(Error::NotFound));
+    }
+
+    #[test]
+    fn blocked_read_should_not_block_other_resources() {
+        let (entered_sender, entered) = mpsc::channel();
+        let (release, release_receiver) = mpsc::channel();
+
+        let res = Resources::new()
+            .mounted_from(
+                "slow", 0, "slow",
+                Blocking {
+                    entered: Mutex::new(entered_sender),
+                    release: Mutex::new(release_receiver),
+                },
+            )
+            .mounted_from(
+                "fast", 0, "fast",
+                backend::InMemory::new()
+                    .with("a", b"hello"),
+            );
+
+        let slow = res.resource("slow/a");
+        let pending = slow.get_async();
+        entered.recv_timeout(Duration::from_secs(10)).expect("blocked read did not start");
+
+        let fast = res.resource("fast/a");
+        assert_eq!(&fast.get().unwrap(), b"hello");
+        fast.write(b"world").unwrap();
+        assert_eq!(&res.resource("fast/a").get().unwrap(), b"world");
+        assert!(res.new_changes().is_some());
+
+        release.send(()).unwrap();
+        assert_eq!(&pending.wait().unwrap(), b"slow");
+    }
+
+    #[test]
+    fn resources_should_be_usable_from_many_threads() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("0", b"0")
+                    .with("1", b"1")
+                    .with("2", b"2")
+                    .with("3", b"3"),
+            );
+
+        let threads: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let res = res.clone();
+                thread::spawn(move || {
+                    for i in 0..200 {
+                        let path = format!("{}", (thread_index + i) % 4);
+                        let proxy = res.resource(&path[..]);
+                        let clone = proxy.clone();
+
+                        if i % 5 == 0 {
+                            clone.write(path.as_bytes()).unwrap();
+                        }
+                        assert_eq!(proxy.get().unwrap(), path.as_bytes());
+                        if i % 7 == 0 {
+                            assert_eq!(clone.get_async().wait().unwrap(), path.as_bytes());
+                        }
+                        assert!(clone.exists());
+
+                        drop(proxy);
+                        if i % 11 == 0 {
+                            if let Some(point) = res.new_changes() {
+                                res.notify_changes_synced(point);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().expect("resource thread panicked");
+        }
+
+        assert_eq!(res.list("").len(), 4);
+        let proxy = res.resource("3");
+        assert_eq!(&proxy.get().unwrap(), b"3");
+        assert!(!proxy.is_modified());
+    }
+
+    #[test]
+    fn shared_contents_should_be_cached_until_resource_is_outdated() {
+        let res = Resources::new()
+            .with_cache_budget(1024)
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello"),
+            );
+
+        let a = res.resource("name");
+        let b = res.resource("name");
+        let first = a.get_shared().unwrap();
+        assert!(Arc::ptr_eq(&first, &b.get_shared().unwrap()));
+        assert_eq!(&a.get().unwrap(), b"hello");
+
+        b.write(b"world").unwrap();
+        assert!(a.is_modified());
+        let second = a.get_shared().unwrap();
+        assert_eq!(&second[..], b"world");
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(!a.is_modified(), "cached read should mark resource read");
+
+        res.insert_loader("b", 1, backend::InMemory::new().with("name", b"again"));
+        assert_eq!(&b.get_shared().unwrap()[..], b"again");
+    }
+
+    #[test]
+    fn contents_over_cache_budget_should_be_evicted() {
+        let res = Resources::new()
+            .with_cache_budget(8)
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("a", b"aaaa")
+                    .with("b", b"bbbb")
+                    .with("c", b"cccc"),
+            );
+
+        let a = res.resource("a");
+        let b = res.resource("b");
+        let c = res.resource("c");
+
+        let a_contents = a.get_shared().unwrap();
+        let b_contents = b.get_shared().unwrap();
+        a.get_shared().unwrap();
+        c.get_shared().unwrap();
+
+        assert!(Arc::ptr_eq(&a_contents, &a.get_shared().unwrap()));
+        assert!(!Arc::ptr_eq(&b_contents, &b.get_shared().unwrap()), "least recently used contents should be evicted");
+    }
+
+    #[test]
+    fn shared_contents_should_not_be_cached_by_default() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello"),
+            );
+        let resource = res.resource("name");
+
+        assert!(!Arc::ptr_eq(&resource.get_shared().unwrap(), &resource.get_shared().unwrap()));
+    }
+
+    #[test]
+    fn hash_should_follow_contents_and_not_mark_resource_read() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello"),
+            );
+        let resource = res.resource("name");
+        let proxy = res.resource("name");
+
+        assert_eq!(backend::content_hash(b"hello"), resource.hash().unwrap());
+
+        proxy.write(b"world").unwrap();
+
+        assert_eq!(backend::content_hash(b"world"), resource.hash().unwrap());
+        assert!(resource.is_modified());
+        assert_eq!(Err(Error::NotFound), res.resource("missing").hash().map_err(Error::into_root));
+    }
+
+    #[test]
+    fn verified_loader_should_report_corrupted_resource() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::Verified::new(
+                    backend::InMemory::new().with("name", b"corrupted"),
+                    backend::Manifest::new().with("name", backend::content_hash(b"hello")),
+                ),
+            );
+
+        match res.resource("name").get().map_err(Error::into_root) {
+            Err(Error::HashMismatch { ref path, .. }) => assert_eq!("name", path.as_clean_str()),
+            other => panic!("expected HashMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn opened_resource_should_stream_from_the_highest_order_loader() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello world")
+                    .with("other", b"other"),
+            )
+            .loaded_from(
+                "b", 1,
+                backend::Compressed::new(backend::InMemory::new(), backend::Codec::Zlib { level: 9 }),
+            );
+        let resource = res.resource("name");
+        res.resource("name").write(b"hello compressed world").unwrap();
+        assert!(resource.is_modified());
+
+        let mut stream = resource.open().unwrap();
+        assert!(!resource.is_modified());
+        stream.seek(SeekFrom::Start(6)).unwrap();
+        let mut contents = String::new();
+        stream.read_to_string(&mut contents).unwrap();
+        assert_eq!("compressed world", contents);
+
+        assert_eq!(&res.resource("other").read_range(1, 3).unwrap(), b"the");
+        assert_eq!(res.resource("missing").read_range(0, 1).map_err(Error::into_root), Err(Error::NotFound));
+    }
+
+    #[test]
+    fn deleting_resource_should_reveal_lower_order_loader_and_then_remove_it() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello"),
+            )
+            .loaded_from(
+                "b", 1,
+                backend::InMemory::new()
+                    .with("name", b"world"),
+            );
+        let resource = res.resource("name");
+        let other = res.resource("name");
+        let changes = res.subscribe();
+
+        assert_eq!(resource.metadata().unwrap().loader_id, "b");
+
+        resource.delete().unwrap();
+        assert!(other.is_modified());
+        assert_eq!(&other.get().unwrap(), b"hello");
+        assert_eq!(
+            resource.metadata().unwrap(),
+            Metadata { len: 5, modified: None, loader_id: String::from("a") }
+        );
+
+        resource.delete().unwrap();
+        assert!(!resource.exists());
+        assert_eq!(resource.delete(), Err(Error::NotFound.in_context(Operation::Delete, "name".into(), None)));
+        assert_eq!(resource.metadata().map_err(Error::into_root), Err(Error::NotFound));
+
+        assert_eq!(
+            changes.try_iter().map(|(_, kind)| kind).collect::<Vec<_>>(),
+            vec![ChangeKind::Modified, ChangeKind::Removed]
+        );
+    }
+
+    #[test]
+    fn renaming_resource_should_move_it_within_or_between_loaders() {
+        let res = Resources::new()
+            .mounted_from(
+                "a", 0, "a",
+                backend::InMemory::new()
+                    .with("a.txt", b"hello"),
+            )
+            .mounted_from(
+                "b", 0, "b",
+                backend::InMemory::new(),
+            );
+        let old = res.resource("a/a.txt");
+        let new = res.resource("a/c.txt");
+        let changes = res.subscribe();
+
+        res.rename("a/a.txt", "a/c.txt").unwrap();
+        assert!(!old.exists());
+        assert!(new.is_modified());
+        assert_eq!(&new.get().unwrap(), b"hello");
+
+        res.rename("a/c.txt", "b/c.txt").unwrap();
+        assert!(!new.exists());
+        assert_eq!(res.resource("b/c.txt").metadata().unwrap().loader_id, "b");
+
+        assert_eq!(res.rename("a/missing.txt", "a/b.txt").map_err(Error::into_root), Err(Error::NotFound));
+        assert_eq!(
+            received_changes(&changes),
+            vec![
+                (String::from("a/a.txt"), ChangeKind::Removed),
+                (String::from("a/c.txt"), ChangeKind::Modified),
+                (String::from("a/c.txt"), ChangeKind::Removed),
+                (String::from("b/c.txt"), ChangeKind::Modified),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_should_follow_loader_write_policies() {
+        let res = Resources::new()
+            .loaded_from(
+                "base", 0,
+                backend::InMemory::new()
+                    .with("config", b"base")
+                    .with("level", b"base"),
+            )
+            .loaded_from("user", 1, backend::InMemory::new())
+            .loaded_from(
+                "mods", 2,
+                backend::InMemory::new()
+                    .with("level", b"mod"),
+            )
+            .with_write_policy("base", WritePolicy::ReadOnly)
+            .with_write_policy("mods", WritePolicy::WriteExisting);
+
+        res.resource("config").write(b"saved").unwrap();
+        res.resource("level").write(b"edited mod").unwrap();
+
+        assert_eq!(res.resource("config").metadata().unwrap().loader_id, "user");
+        assert_eq!(res.resource("level").metadata().unwrap().loader_id, "mods");
+        assert_eq!(&res.resource("level").get().unwrap(), b"edited mod");
+
+        res.remove_loader("user");
+        res.remove_loader("mods");
+        assert_eq!(&res.resource("config").get().unwrap(), b"base");
+        assert_eq!(&res.resource("level").get().unwrap(), b"base");
+
+        match res.resource("config").write(b"saved").map_err(Error::into_root) {
+            Err(Error::NotWritable) => (),
+            _ => panic!("expected NotWritable"),
+        }
+        match res.resource("config").delete().map_err(Error::into_root) {
+            Err(Error::NotWritable) => (),
+            _ => panic!("expected NotWritable"),
+        }
+    }
+
+    #[test]
+    fn write_should_skip_loaders_that_can_not_write_by_default() {
+        let res = Resources::new()
+            .loaded_from("base", 0, backend::InMemory::new().with("config", b"base"))
+            .loaded_from("overrides", 1, Unwritable);
+
+        res.resource("config").write(b"saved").unwrap();
+        assert_eq!(&res.resource("config").get().unwrap(), b"saved");
+        assert_eq!(res.resource("config").metadata().unwrap().loader_id, "base");
+    }
+
+    #[test]
+    fn write_should_not_fall_through_an_explicit_write_target_that_can_not_write() {
+        let res = Resources::new()
+            .loaded_from("base", 0, backend::InMemory::new().with("config", b"base"))
+            .loaded_from("overrides", 1, Unwritable)
+            .with_write_policy("overrides", WritePolicy::WriteTarget);
+
+        assert_eq!(
+            res.resource("config").write(b"saved"),
+            Err(Error::NotWritable.in_context(Operation::Write, "config".into(), Some("overrides")))
+        );
+        assert_eq!(&res.resource("config").get().unwrap(), b"base");
+    }
+
+    #[test]
+    fn write_to_should_write_to_the_loader_regardless_of_order() {
+        let res = Resources::new()
+            .loaded_from("low", 0, backend::InMemory::new())
+            .loaded_from(
+                "high", 1,
+                backend::InMemory::new()
+                    .with("name", b"high"),
+            )
+            .mounted_from("mounted", 2, "other", backend::InMemory::new())
+            .with_write_policy("high", WritePolicy::ReadOnly);
+        let resource = res.resource("name");
+        let other = res.resource("name");
+
+        let changes = res.subscribe();
+        resource.write_to("low", b"low").unwrap();
+        // the write is hidden by "high", so the served contents did not change
+        assert!(!other.is_modified());
+        assert_eq!(received_changes(&changes), vec![]);
+        assert_eq!(&other.get().unwrap(), b"high");
+
+        res.remove_loader("high");
+        assert!(other.is_modified());
+        assert_eq!(&other.get().unwrap(), b"low");
+
+        resource.write_to("low", b"low again").unwrap();
+        assert!(other.is_modified());
+        assert_eq!(received_changes(&changes), vec![
+            (String::from("name"), ChangeKind::Modified),
+            (String::from("name"), ChangeKind::Modified),
+        ]);
+
+        res.set_write_policy("low", WritePolicy::ReadOnly);
+        assert_eq!(
+            resource.write_to("low", b"again"),
+            Err(Error::NotWritable.in_context(Operation::Write, "name".into(), Some("low")))
+        );
+        assert_eq!(resource.write_to("mounted", b"other").map_err(Error::into_root), Err(Error::NotFound));
+        assert_eq!(resource.write_to("missing", b"other").map_err(Error::into_root), Err(Error::NotFound));
+    }
+
+    #[test]
+    fn access_stats_should_count_reads_per_path_and_loader() {
+        let res = Resources::new()
+            .loaded_from("low", 0, backend::InMemory::new().with("a", b"hello").with("b", b"hi"))
+            .loaded_from("high", 1, backend::InMemory::new().with("b", b"world"))
+            .with_cache_budget(1024);
+        assert_eq!(None, res.access_stats());
+
+        let res = res.with_access_stats();
+        let a = res.resource("a");
+        let b = res.resource("b");
+        a.get().unwrap();
+        a.get().unwrap();
+        b.get().unwrap();
+        a.read_range(1, 2).unwrap();
+        assert!(res.resource("missing").get().is_err());
+
+        let stats = res.access_stats().unwrap();
+        let a_counts = &stats.paths[&ResourcePathBuf::from(String::from("a"))];
+        assert_eq!((2, 7, 1, 1), (a_counts.reads, a_counts.bytes, a_counts.cache_hits, a_counts.cache_misses));
+        assert_eq!(5, stats.paths[&ResourcePathBuf::from(String::from("b"))].bytes);
+        assert_eq!(2, stats.loaders["low"].reads);
+        assert_eq!(1, stats.loaders["high"].reads);
+        assert_eq!(0, stats.paths[&ResourcePathBuf::from(String::from("missing"))].reads);
+
+        let mut dump = Vec::new();
+        res.dump_access_stats(&mut dump).unwrap();
+        let dump = String::from_utf8(dump).unwrap();
+        assert!(dump.lines().any(|line| line.ends_with("  high")));
+        assert!(dump.lines().any(|line| line.ends_with("  a")));
+
+        res.reset_access_stats();
+        assert_eq!(Some(AccessStats::default()), res.access_stats());
+    }
+
+    #[test]
+    fn access_stats_should_not_count_cache_use_without_cache() {
+        let res = Resources::new()
+            .loaded_from("a", 0, backend::InMemory::new().with("a", b"hello"))
+            .with_access_stats();
+        let a = res.resource("a");
+        a.get().unwrap();
+        a.get().unwrap();
+
+        let stats = res.access_stats().unwrap();
+        let a_counts = &stats.paths[&ResourcePathBuf::from(String::from("a"))];
+        assert_eq!((2, 0, 0), (a_counts.reads, a_counts.cache_hits, a_counts.cache_misses));
+    }
+
+    fn received_changes(changes: &mpsc::Receiver<(ResourcePathBuf, ChangeKind)>) -> Vec<(String, ChangeKind)> {
+        let mut changes: Vec<_> = changes.try_iter()
+            .map(|(path, kind)| (path.to_string(), kind))
+            .collect();
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+    }
+
+    #[test]
+    fn subscriber_should_receive_writes() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("name", b"hello"),
+            );
+        let changes = res.subscribe();
+
+        res.resource("name").write(b"world").unwrap();
+
+        assert_eq!(received_changes(&changes), vec![(String::from("name"), ChangeKind::Modified)]);
+    }
+
+    #[test]
+    fn subscriber_should_receive_changes_of_inserted_and_removed_loaders() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("both", b"a")
+                    .with("only_a", b"a"),
+            )
+            .loaded_from(
+                "top", 2,
+                backend::InMemory::new()
+                    .with("top", b"top"),
+            );
+        let changes = res.subscribe();
+
+        res.insert_loader(
+            "b", 1,
+            backend::InMemory::new()
+                .with("both", b"b")
+                .with("only_b", b"b")
+                .with("top", b"b"),
+        );
+
+        assert_eq!(
+            received_changes(&changes),
+            vec![
+                (String::from("both"), ChangeKind::Overridden),
+                (String::from("only_b"), ChangeKind::Modified),
+            ]
+        );
+
+        res.remove_loader("b");
+
+        assert_eq!(
+            received_changes(&changes),
+            vec![
+                (String::from("both"), ChangeKind::Modified),
+                (String::from("only_b"), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn dropped_subscriber_should_not_prevent_changes() {
+        let res = Resources::new()
+            .loaded_from("a", 0, backend::InMemory::new());
+        let kept = res.subscribe();
+        drop(res.subscribe());
+
+        res.resource("name").write(b"hello").unwrap();
+
+        assert_eq!(received_changes(&kept), vec![(String::from("name"), ChangeKind::Modified)]);
+    }
+
+    #[test]
+    fn listing_should_merge_paths_from_all_loaders() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("objs/dice.obj", b"a")
+                    .with("objs/dice.mtl", b"a")
+                    .with("shaders/shiny.vert", b"a"),
+            )
+            .loaded_from(
+                "b", 1,
+                backend::InMemory::new()
+                    .with("objs/dice.obj", b"b")
+                    .with("objs/more/cube.obj", b"b"),
+            );
+
+        assert_eq!(
+            res.list("objs"),
+            vec![
+                ResourcePathBuf::from(String::from("objs/dice.mtl")),
+                ResourcePathBuf::from(String::from("objs/dice.obj")),
+                ResourcePathBuf::from(String::from("objs/more/cube.obj")),
+            ]
+        );
+        assert_eq!(res.list("").len(), 4);
+        assert!(res.list("obj").is_empty());
+    }
+
+    #[test]
+    fn mounted_loader_should_serve_only_paths_under_mount_path() {
+        let res = Resources::new()
+            .mounted_from(
+                "core", 0, "core/",
+                backend::InMemory::new()
+                    .with("Config.toml", b"core config")
+                    .with("shaders/shiny.vert", b"shader"),
+            )
+            .mounted_from(
+                "generated", 0, "generated",
+                backend::InMemory::new()
+                    .with("Config.toml", b"generated config"),
+            );
+
+        assert_eq!(&res.resource("core/Config.toml").get().unwrap(), b"core config");
+        assert_eq!(&res.resource("generated/Config.toml").get().unwrap(), b"generated config");
+        assert!(!res.resource("Config.toml").exists());
+        assert!(res.resource("Config.toml").get().is_err());
+        assert!(!res.resource("cor/Config.toml").exists());
+
+        assert_eq!(
+            res.list(""),
+            vec![
+                ResourcePathBuf::from(String::from("core/Config.toml")),
+                ResourcePathBuf::from(String::from("core/shaders/shiny.vert")),
+                ResourcePathBuf::from(String::from("generated/Config.toml")),
+            ]
+        );
+        assert_eq!(
+            res.list("core/shaders"),
+            vec![ResourcePathBuf::from(String::from("core/shaders/shiny.vert"))]
+        );
+    }
+
+    #[test]
+    fn writing_to_mounted_loader_should_strip_mount_path() {
+        let backend = backend::InMemory::new();
+        let res = Resources::new()
+            .mounted_from("generated", 0, "generated", backend);
+
+        res.resource("generated/a/b.txt").write(b"hello").unwrap();
+
+        assert_eq!(res.list("generated/a"), vec![ResourcePathBuf::from(String::from("generated/a/b.txt"))]);
+        assert!(res.resource("generated/a/b.txt").exists());
+        assert!(res.resource("b.txt").write(b"hello").is_err());
+    }
+
+    #[test]
+    fn adding_mounted_loader_should_invalidate_only_resources_under_mount_path() {
+        let res = Resources::new()
+            .loaded_from(
+                "a", 0,
+                backend::InMemory::new()
+                    .with("core/name", b"hello")
+                    .with("name", b"hello"),
+            );
+
+        let inside = res.resource("core/name");
+        let outside = res.resource("name");
+
+        res.insert_mounted_loader("b", 1, "core", backend::InMemory::new().with("name", b"world"));
+
+        assert!(inside.is_modified());
+        assert!(!outside.is_modified());
+        assert_eq!(&inside.get().unwrap(), b"world");
+
+        res.remove_loader("b");
+
+        assert!(inside.is_modified());
+        assert!(!outside.is_modified());
+        assert_eq!(&inside.get().unwrap(), b"hello");
+    }
+
     #[test]
     fn adding_the_loader_should_override_resource_and_invalidate_it() {
         let res = Resources::new()
@@ -92,16 +92,91 @@ impl ResourcePath {
         self.inner.split('/')
     }
 
-    /// Returns path as str and ensures that the returned str does not have a leading or trailing slash
+    /// Returns path as str and ensures that the returned str does not have leading or trailing slashes
     pub fn as_clean_str(&self) -> &str {
-        let mut result = &self.inner;
-        if result.starts_with('/') {
-            result = &result[1..];
+        self.inner.trim_matches('/')
+    }
+
+    /// Returns the path with `\` separators replaced by `/`, empty and `.` items removed,
+    /// and `..` items applied to the previous item.
+    ///
+    /// Returns `None` if `..` items lead outside of the root.
+    pub fn normalize(&self) -> Option<ResourcePathBuf> {
+        let mut items = Vec::new();
+        for item in self.inner.split(|c| c == '/' || c == '\\') {
+            match item {
+                "" | "." => (),
+                ".." => {
+                    items.pop()?;
+                },
+                item => items.push(item),
+            }
+        }
+
+        Some(ResourcePathBuf {
+            inner: items.join("/")
+        })
+    }
+
+    /// Returns the last item of the path, or `None` if the path is empty or ends with `..`.
+    pub fn file_name(&self) -> Option<&str> {
+        self.as_clean_str().rsplit('/').next()
+            .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+    }
+
+    /// Returns the part of the file name after the last `.`.
+    ///
+    /// File names that start with `.` and have no other `.` have no extension.
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name()?;
+        match name.rfind('.') {
+            None | Some(0) => None,
+            Some(index) => Some(&name[index + 1..]),
+        }
+    }
+
+    /// Returns the path with the extension of the file name replaced,
+    /// or removed if the `extension` is empty.
+    pub fn with_extension(&self, extension: &str) -> ResourcePathBuf {
+        let path = self.as_clean_str();
+        if self.file_name().is_none() {
+            return ResourcePathBuf::from(path.as_ref());
+        }
+
+        let stem = match self.extension() {
+            Some(current) => &path[..path.len() - current.len() - 1],
+            None => path,
+        };
+
+        if extension.is_empty() {
+            ResourcePathBuf::from(stem.as_ref())
+        } else {
+            ResourcePathBuf {
+                inner: [stem, ".", extension].concat()
+            }
+        }
+    }
+
+    /// Returns the rest of the path if it is equal to the prefix or is inside the prefix directory.
+    pub fn strip_prefix<P: AsRef<ResourcePath>>(&self, prefix: P) -> Option<&ResourcePath> {
+        let path = self.as_clean_str();
+        let prefix = prefix.as_ref().as_clean_str();
+
+        if prefix.is_empty() {
+            return Some(ResourcePath::from_inner(path));
+        }
+        if !path.starts_with(prefix) {
+            return None;
         }
-        if result.ends_with('/') {
-            result = &result[..1];
+
+        let rest = &path[prefix.len()..];
+        if rest.is_empty() {
+            Some(ResourcePath::from_inner(rest))
+        } else if rest.starts_with('/') {
+            Some(ResourcePath::from_inner(&rest[1..]))
+        } else {
+            None
         }
-        result
     }
 
     pub fn join<P: AsRef<ResourcePath>>(&self, other: P) -> ResourcePathBuf {
@@ -147,5 +222,54 @@ impl fmt::Display for ResourcePathBuf {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         fmt::Display::fmt(&self.inner, f)
     }
-}
+}
+
+#[cfg(test)]
+mod test {
+    use super::ResourcePath;
 
+    fn path(path: &str) -> &ResourcePath {
+        ResourcePath::from_inner(path)
+    }
+
+    fn normalized(p: &str) -> Option<String> {
+        path(p).normalize().map(|path| path.to_string())
+    }
+
+    #[test]
+    fn normalize_should_remove_empty_and_current_items_and_apply_parent_items() {
+        assert_eq!(Some("a/b/c".into()), normalized("/a//b/./c/"));
+        assert_eq!(Some("a/c".into()), normalized("a/b/../c"));
+        assert_eq!(Some("a/b".into()), normalized("a\\b"));
+        assert_eq!(Some("".into()), normalized("a/.."));
+    }
+
+    #[test]
+    fn normalize_should_reject_paths_outside_of_the_root() {
+        assert_eq!(None, normalized(".."));
+        assert_eq!(None, normalized("a/../../b"));
+        assert_eq!(None, normalized("..\\b"));
+    }
+
+    #[test]
+    fn as_clean_str_should_trim_slashes() {
+        assert_eq!("a/b", path("/a/b/").as_clean_str());
+        assert_eq!("", path("/").as_clean_str());
+    }
+
+    #[test]
+    fn file_name_and_extension() {
+        assert_eq!(Some("c.tar.gz"), path("a/b/c.tar.gz").file_name());
+        assert_eq!(Some("gz"), path("a/b/c.tar.gz").extension());
+        assert_eq!(None, path("a/.hidden").extension());
+        assert_eq!(None, path("a/..").file_name());
+        assert_eq!(None, path("").file_name());
+    }
+
+    #[test]
+    fn with_extension_should_replace_or_remove_extension() {
+        assert_eq!("a/b.frag", path("a/b.vert").with_extension("frag").to_string());
+        assert_eq!("a/b.vert", path("a/b").with_extension("vert").to_string());
+        assert_eq!("a/b", path("a/b.vert").with_extension("").to_string());
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender, Receiver, TryRecvError};
+use std::thread;
+use std::time::Instant;
+use shared::UserKey;
+use {Resource, Error};
+
+pub const DEFAULT_READ_WORKERS: usize = 2;
+
+type Job = Box<FnOnce() + Send>;
+
+/// Threads that run backend reads for `Resource::get_async`.
+///
+/// Threads are started on the first read, and stop when the pool is dropped.
+pub struct ReadPool {
+    worker_count: usize,
+    sender: Mutex<Option<Sender<Job>>>,
+}
+
+impl ReadPool {
+    pub fn new(worker_count: usize) -> ReadPool {
+        ReadPool {
+            worker_count: ::std::cmp::max(1, worker_count),
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// Queues the job for the next free worker.
+    ///
+    /// Fails if all workers have stopped, for example after panics in jobs. The workers are
+    /// started again on the next call.
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) -> Result<(), Error> {
+        let mut sender = self.sender.lock().expect("failed to lock ReadPool");
+
+        if sender.is_none() {
+            *sender = Some(self.spawn_workers());
+        }
+
+        let sent = sender.as_ref()
+            .expect("expected ReadPool workers to be started")
+            .send(Box::new(job));
+        if sent.is_err() {
+            *sender = None;
+            return Err(worker_stopped());
+        }
+        Ok(())
+    }
+
+    fn spawn_workers(&self) -> Sender<Job> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for i in 0..self.worker_count {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("resources-read-{}", i))
+                .spawn(move || loop {
+                    let job = receiver.lock().expect("failed to lock ReadPool receiver").recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+                .expect("failed to spawn ReadPool worker");
+        }
+
+        sender
+    }
+}
+
+/// Read started with `Resource::get_async`.
+///
+/// Does not borrow the `Resource`, so it can be stored or sent to another thread.
+pub struct PendingRead {
+    /// Keeps the resource alive until the read is finished.
+    resource: Resource,
+    /// User that started the read, it is marked as read instead of the `resource` clone.
+    reader: UserKey,
+    outdated_at: Option<Instant>,
+    receiver: Receiver<Result<Vec<u8>, Error>>,
+    finished: bool,
+}
+
+impl PendingRead {
+    pub fn new(resource: &Resource, outdated_at: Option<Instant>, receiver: Receiver<Result<Vec<u8>, Error>>) -> PendingRead {
+        PendingRead {
+            resource: resource.clone(),
+            reader: resource.key,
+            outdated_at,
+            receiver,
+            finished: false,
+        }
+    }
+
+    /// Returns the result once the read is finished, and `None` while it is in progress or after
+    /// the result was returned.
+    pub fn poll(&mut self) -> Option<Result<Vec<u8>, Error>> {
+        if self.finished {
+            return None;
+        }
+
+        let result = match self.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(worker_stopped()),
+        };
+
+        Some(self.finish(result))
+    }
+
+    /// Blocks until the read is finished.
+    pub fn wait(mut self) -> Result<Vec<u8>, Error> {
+        let result = self.receiver.recv().unwrap_or_else(|_| Err(worker_stopped()));
+        self.finish(result)
+    }
+
+    fn finish(&mut self, result: Result<Vec<u8>, Error>) -> Result<Vec<u8>, Error> {
+        self.finished = true;
+        if result.is_ok() {
+            self.resource.notify_user_did_read(self.reader, self.outdated_at);
+        }
+        result
+    }
+}
+
+fn worker_stopped() -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::Other, "resource read worker stopped"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReadPool;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn execute_should_fail_when_workers_stopped_and_start_them_again() {
+        let pool = ReadPool::new(1);
+        pool.execute(|| panic!("job failed on purpose")).unwrap();
+
+        let mut attempts = 0;
+        while pool.execute(|| ()).is_ok() {
+            attempts += 1;
+            assert!(attempts < 500, "expected the worker to stop");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        pool.execute(move || sender.send(42).unwrap()).unwrap();
+        assert_eq!(42, receiver.recv().unwrap());
+    }
+}
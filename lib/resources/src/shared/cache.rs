@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasherDefault;
+use std::sync::Arc;
+use twox_hash::XxHash;
+
+struct CacheEntry {
+    version: u64,
+    data: Arc<[u8]>,
+    last_use: u64,
+}
+
+/// Resource contents keyed by resource id, limited by the byte budget.
+///
+/// When the budget is exceeded, the least recently used contents are dropped. Contents stay
+/// in memory while they are referenced outside of the cache, but they are not counted.
+pub struct ByteCache {
+    budget: usize,
+    size: usize,
+    next_use: u64,
+    entries: HashMap<usize, CacheEntry, BuildHasherDefault<XxHash>>,
+    uses: BTreeMap<u64, usize>,
+}
+
+impl ByteCache {
+    pub fn new(budget: usize) -> ByteCache {
+        ByteCache {
+            budget,
+            size: 0,
+            next_use: 0,
+            entries: HashMap::default(),
+            uses: BTreeMap::new(),
+        }
+    }
+
+    pub fn can_fit(&self, len: usize) -> bool {
+        len <= self.budget
+    }
+
+    /// Returns false if the budget is 0, in which case nothing is ever cached.
+    pub fn is_enabled(&self) -> bool {
+        self.budget > 0
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict_to(budget);
+    }
+
+    /// Returns contents if they were cached for the same resource version.
+    pub fn get(&mut self, resource_id: usize, version: u64) -> Option<Arc<[u8]>> {
+        let is_current = match self.entries.get(&resource_id) {
+            Some(entry) => entry.version == version,
+            None => return None,
+        };
+        if !is_current {
+            self.remove(resource_id);
+            return None;
+        }
+
+        let use_id = self.next_use();
+        let entry = self.entries.get_mut(&resource_id).expect("expected cache entry to exist");
+        self.uses.remove(&entry.last_use);
+        self.uses.insert(use_id, resource_id);
+        entry.last_use = use_id;
+
+        Some(entry.data.clone())
+    }
+
+    pub fn insert(&mut self, resource_id: usize, version: u64, data: Arc<[u8]>) {
+        self.remove(resource_id);
+        if !self.can_fit(data.len()) {
+            return;
+        }
+
+        let budget = self.budget;
+        self.evict_to(budget - data.len());
+
+        let use_id = self.next_use();
+        self.size += data.len();
+        self.uses.insert(use_id, resource_id);
+        self.entries.insert(resource_id, CacheEntry {
+            version,
+            data,
+            last_use: use_id,
+        });
+    }
+
+    pub fn remove(&mut self, resource_id: usize) {
+        if let Some(entry) = self.entries.remove(&resource_id) {
+            self.uses.remove(&entry.last_use);
+            self.size -= entry.data.len();
+        }
+    }
+
+    fn evict_to(&mut self, size: usize) {
+        while self.size > size {
+            let least_recent = match self.uses.iter().next() {
+                Some((_, resource_id)) => *resource_id,
+                None => return,
+            };
+            self.remove(least_recent);
+        }
+    }
+
+    fn next_use(&mut self) -> u64 {
+        self.next_use += 1;
+        self.next_use
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ByteCache;
+    use std::sync::Arc;
+
+    fn data(value: &[u8]) -> Arc<[u8]> {
+        Arc::from(value)
+    }
+
+    #[test]
+    fn least_recently_used_contents_should_be_evicted() {
+        let mut cache = ByteCache::new(10);
+
+        cache.insert(1, 0, data(b"aaaa"));
+        cache.insert(2, 0, data(b"bbbb"));
+        assert!(cache.get(1, 0).is_some());
+
+        cache.insert(3, 0, data(b"cccc"));
+
+        assert!(cache.get(1, 0).is_some());
+        assert!(cache.get(2, 0).is_none());
+        assert!(cache.get(3, 0).is_some());
+        assert_eq!(8, cache.size);
+    }
+
+    #[test]
+    fn contents_of_other_version_or_over_budget_should_not_be_returned() {
+        let mut cache = ByteCache::new(4);
+
+        cache.insert(1, 0, data(b"aaaa"));
+        assert!(cache.get(1, 1).is_none());
+        assert!(cache.get(1, 0).is_none(), "outdated contents should be removed");
+
+        cache.insert(1, 1, data(b"aaaaa"));
+        assert!(cache.get(1, 1).is_none());
+        assert_eq!(0, cache.size);
+
+        cache.insert(1, 2, data(b"aaaa"));
+        cache.set_budget(2);
+        assert!(cache.get(1, 2).is_none());
+    }
+}
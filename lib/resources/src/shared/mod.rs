@@ -1,15 +1,25 @@
 use std::time::Instant;
 use std::collections::HashMap;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::hash::BuildHasherDefault;
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard, TryLockError};
+use std::sync::mpsc::Receiver;
 use twox_hash::XxHash;
 use path::{ResourcePath, ResourcePathBuf};
 use slab::Slab;
 use backend::{Backend, BackendSyncPoint};
+use Error;
 
 mod resource_metadata;
+mod subscribers;
+mod cache;
+mod stats;
 
 use self::resource_metadata::{ResourceMetadata, ResourceUserMetadata};
+use self::subscribers::Subscribers;
+use self::cache::ByteCache;
+pub use self::subscribers::ChangeKind;
+pub use self::stats::{AccessStats, AccessCounts, StatsRecorder};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct LoaderKey {
@@ -32,6 +42,245 @@ impl PartialOrd for LoaderKey {
     }
 }
 
+/// Backend that can be used without locking `SharedResources`.
+///
+/// Reads take the read lock, so they can run in parallel, while writes and change tracking
+/// take the write lock.
+pub type SharedBackend = Arc<RwLock<Box<Backend>>>;
+
+/// Locks the backend to check for changes, returns `None` if the backend is busy reading.
+///
+/// Busy backends are checked on the next `new_changes`, so that a slow read does not block
+/// every other resource while `SharedResources` is locked for write.
+fn try_lock_for_changes(backend: &SharedBackend) -> Option<RwLockWriteGuard<Box<Backend>>> {
+    match backend.try_write() {
+        Ok(backend) => Some(backend),
+        Err(TryLockError::WouldBlock) => None,
+        Err(TryLockError::Poisoned(_)) => panic!("failed to lock backend for write"),
+    }
+}
+
+/// The way `Resource::write` and other modifications treat the loader.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WritePolicy {
+    /// The loader is never modified, even if the backend can write, and writes go to loaders
+    /// with a lower order. This is the default for backends that can not write.
+    ReadOnly,
+    /// Resources that the loader contains are modified in place, writes of other resources
+    /// go to loaders with a lower order.
+    WriteExisting,
+    /// All writes under the mount path go to the loader, so it works as a copy-on-write overlay
+    /// and loaders with a lower order are never modified. If the backend can not write, the
+    /// write fails with `NotWritable`. This is the default for backends that can write.
+    WriteTarget,
+}
+
+/// Backend that serves resources under the mount path.
+#[derive(Clone)]
+struct Mount {
+    path: ResourcePathBuf,
+    write_policy: WritePolicy,
+    backend: SharedBackend,
+}
+
+impl Mount {
+    /// Returns the path in the backend if the resource is under the mount path.
+    fn backend_path<'p>(&self, path: &'p ResourcePath) -> Option<&'p ResourcePath> {
+        path.strip_prefix(&self.path)
+    }
+
+    fn exists(&self, path: &ResourcePath) -> bool {
+        match self.backend_path(path) {
+            Some(path) => self.backend.read().expect("failed to lock backend for read").exists(path),
+            None => false,
+        }
+    }
+
+    /// Returns paths of all resources in this backend, including the mount path.
+    fn list_all(&self) -> Vec<ResourcePathBuf> {
+        self.backend.read().expect("failed to lock backend for read")
+            .list("".into())
+            .into_iter()
+            .map(|path| self.path.join(path))
+            .collect()
+    }
+}
+
+/// Loaders at the moment `SharedResources::mounts` was called.
+///
+/// Backends are locked through this copy after the lock of `SharedResources` is released, so
+/// that a slow backend does not block access to other resources.
+pub struct Mounts {
+    /// Ordered from the lowest order.
+    mounts: Vec<(LoaderKey, Mount)>,
+}
+
+impl Mounts {
+    /// Returns the key of the loader with the highest order that contains the resource.
+    fn serving_loader(&self, path: &ResourcePath) -> Option<&LoaderKey> {
+        self.mounts.iter().rev()
+            .find(|(_, mount)| mount.exists(path))
+            .map(|(key, _)| key)
+    }
+
+    /// Returns the loader with the highest order that contains the resource.
+    pub fn serving_backend(&self, path: &ResourcePath) -> Option<ServingBackend> {
+        let path = &resource_key(path).ok()?;
+        self.mounts.iter().rev()
+            .filter_map(|(key, mount)|
+                mount.backend_path(path)
+                    .filter(|backend_path| mount.backend.read().expect("failed to lock backend for read").exists(backend_path))
+                    .map(|backend_path| ServingBackend {
+                        loader_id: key.id.clone(),
+                        mount_path: mount.path.clone(),
+                        path: backend_path.into(),
+                        write_policy: mount.write_policy,
+                        backend: mount.backend.clone(),
+                    })
+            )
+            .next()
+    }
+
+    /// Returns the change that subscribers receive after the resource was deleted or renamed.
+    pub fn change_after_removal(&self, path: &ResourcePath) -> ChangeKind {
+        let served = resource_key(path)
+            .map(|path| self.serving_loader(&path).is_some())
+            .unwrap_or(false);
+        if served {
+            ChangeKind::Modified
+        } else {
+            ChangeKind::Removed
+        }
+    }
+
+    pub fn list(&self, dir: &ResourcePath) -> Vec<ResourcePathBuf> {
+        let dir = match resource_key(dir) {
+            Ok(dir) => dir,
+            Err(_) => return Vec::new(),
+        };
+        let dir = &dir;
+        let mut paths = BTreeSet::new();
+        for (_, mount) in self.mounts.iter().rev() {
+            let backend_dir = match dir.strip_prefix(&mount.path) {
+                Some(backend_dir) => backend_dir,
+                // the whole mount is inside the listed directory
+                None if mount.path.strip_prefix(dir).is_some() => "".into(),
+                None => continue,
+            };
+            let backend_paths = mount.backend.read().expect("failed to lock backend for read").list(backend_dir);
+            paths.extend(
+                backend_paths.into_iter()
+                    .map(|path| mount.path.join(path))
+            );
+        }
+        paths.into_iter().collect()
+    }
+
+    /// Takes paths changed outside of this process, with the changes to send to subscribers.
+    ///
+    /// Busy backends are skipped, their changes are taken on the next call.
+    pub fn take_modified_paths(&self) -> PathChanges {
+        let mut modified_paths = Vec::new();
+        for (key, mount) in self.mounts.iter() {
+            if let Some(mut backend) = try_lock_for_changes(&mount.backend) {
+                modified_paths.extend(
+                    backend.take_modified_paths().into_iter()
+                        .map(|path| (key.clone(), mount.path.join(path)))
+                );
+            }
+        }
+
+        let mut changes = PathChanges::default();
+        for (key, path) in modified_paths {
+            match self.serving_loader(&path) {
+                None => changes.sent.push((path.clone(), ChangeKind::Removed)),
+                Some(serving_key) if serving_key <= &key => changes.sent.push((path.clone(), ChangeKind::Modified)),
+                // the change is hidden by a loader with a higher order
+                Some(_) => (),
+            }
+            changes.outdated.push(path);
+        }
+        changes
+    }
+
+    /// Prepares the loader for `SharedResources::insert_loader`.
+    pub fn new_loader<L: Backend + 'static>(&self, loader_id: &str, order: isize, mount_path: &ResourcePath, backend: L) -> NewLoader {
+        let key = LoaderKey { id: loader_id.into(), order };
+        let write_policy = if backend.can_write() { WritePolicy::WriteTarget } else { WritePolicy::ReadOnly };
+        let mount = Mount {
+            path: resource_key(mount_path).expect("mount path leads outside of the root"),
+            write_policy,
+            backend: Arc::new(RwLock::new(Box::new(backend) as Box<Backend>)),
+        };
+        let mut changes = Vec::new();
+        for path in mount.list_all() {
+            match self.serving_loader(&path) {
+                None => changes.push((path, ChangeKind::Modified)),
+                Some(serving_key) if serving_key < &key => changes.push((path, ChangeKind::Overridden)),
+                // the new resource is hidden by a loader with a higher order
+                Some(_) => (),
+            }
+        }
+        NewLoader { key, mount, changes }
+    }
+
+    /// Returns the changes made by the removal of these loaders, which were removed with
+    /// `SharedResources::remove_loader`, given the loaders that remain and the resource paths.
+    pub fn removal_changes(&self, remaining: &Mounts, resource_paths: &[ResourcePathBuf]) -> PathChanges {
+        let mut changes = PathChanges::default();
+        for (removed_key, removed_mount) in self.mounts.iter() {
+            changes.outdated.extend(
+                resource_paths.iter()
+                    .filter(|path| removed_mount.exists(path))
+                    .cloned()
+            );
+            for path in removed_mount.list_all() {
+                match remaining.serving_loader(&path) {
+                    None => changes.sent.push((path, ChangeKind::Removed)),
+                    Some(serving_key) if serving_key < removed_key => changes.sent.push((path, ChangeKind::Modified)),
+                    // the removed resource was hidden by a loader with a higher order
+                    Some(_) => (),
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Loader that is ready to be inserted, with the changes that its insertion makes.
+pub struct NewLoader {
+    key: LoaderKey,
+    mount: Mount,
+    changes: Vec<(ResourcePathBuf, ChangeKind)>,
+}
+
+/// Paths of resources to outdate, and the changes to send to subscribers.
+#[derive(Default)]
+pub struct PathChanges {
+    outdated: Vec<ResourcePathBuf>,
+    sent: Vec<(ResourcePathBuf, ChangeKind)>,
+}
+
+/// Backends that may contain the resource, ordered from the highest order.
+pub struct ResourceBackends {
+    pub path: ResourcePathBuf,
+    pub modification_time: Option<Instant>,
+    pub version: u64,
+    pub backends: Vec<ServingBackend>,
+    /// Recorder of backend reads, if access statistics are enabled.
+    pub stats: Option<Arc<StatsRecorder>>,
+}
+
+/// Loader that serves the resource.
+pub struct ServingBackend {
+    pub loader_id: String,
+    pub mount_path: ResourcePathBuf,
+    /// Path of the resource in the backend.
+    pub path: ResourcePathBuf,
+    pub write_policy: WritePolicy,
+    pub backend: SharedBackend,
+}
+
 #[derive(Copy, Clone)]
 pub struct UserKey {
     pub resource_id: usize,
@@ -52,8 +301,20 @@ pub enum InternalSyncPoint {
 pub struct SharedResources {
     resource_metadata: Slab<ResourceMetadata>,
     path_resource_ids: HashMap<ResourcePathBuf, usize, BuildHasherDefault<XxHash>>,
-    backends: BTreeMap<LoaderKey, Box<Backend>>,
+    backends: BTreeMap<LoaderKey, Mount>,
     outdated_at: Option<Instant>,
+    subscribers: Subscribers,
+    cache: Mutex<ByteCache>,
+    stats: Option<Arc<StatsRecorder>>,
+}
+
+/// Returns the normalized path that identifies the resource.
+///
+/// Fails with `PathOutsideRoot` if the path leads outside of the root, so that no backend
+/// ever receives such path.
+pub fn resource_key(path: &ResourcePath) -> Result<ResourcePathBuf, Error> {
+    path.normalize()
+        .ok_or_else(|| Error::PathOutsideRoot { path: ResourcePathBuf::from(String::from(path.as_clean_str())) })
 }
 
 fn backend_hash(id: &str) -> u64 {
@@ -70,15 +331,90 @@ impl SharedResources {
             path_resource_ids: HashMap::default(),
             backends: BTreeMap::new(),
             outdated_at: None,
+            subscribers: Subscribers::new(),
+            cache: Mutex::new(ByteCache::new(0)),
+            stats: None,
         }
     }
 
+    pub fn set_cache_budget(&mut self, budget: usize) {
+        self.cache.lock()
+            .expect("failed to lock cache")
+            .set_budget(budget);
+    }
+
+    /// Starts recording access statistics, keeps the recorded statistics if already enabled.
+    pub fn enable_stats(&mut self) {
+        if self.stats.is_none() {
+            self.stats = Some(Arc::new(StatsRecorder::new()));
+        }
+    }
+
+    pub fn stats(&self) -> Option<&StatsRecorder> {
+        self.stats.as_ref().map(|stats| &**stats)
+    }
+
+    /// Returns cached contents if the resource was not outdated since they were cached.
+    ///
+    /// Cache use is not recorded in stats while the cache is disabled.
+    pub fn cached(&self, key: UserKey) -> Option<Arc<[u8]>> {
+        let metadata = self.resource_metadata.get(key.resource_id)?;
+        let (data, cache_enabled) = {
+            let mut cache = self.cache.lock()
+                .expect("failed to lock cache");
+            (cache.get(key.resource_id, metadata.version), cache.is_enabled())
+        };
+        match self.stats {
+            Some(ref stats) if cache_enabled => stats.record_cache_use(&metadata.path, data.is_some()),
+            _ => (),
+        }
+        data
+    }
+
+    /// Caches contents of `len` bytes that were read at the resource version.
+    ///
+    /// Contents are not created if they do not fit in the budget, and are dropped if the resource
+    /// was outdated during the read.
+    pub fn insert_cached<F: FnOnce() -> Arc<[u8]>>(&self, key: UserKey, version: u64, len: usize, data: F) {
+        match self.resource_metadata.get(key.resource_id) {
+            Some(metadata) if metadata.version == version => (),
+            _ => return,
+        }
+        let mut cache = self.cache.lock()
+            .expect("failed to lock cache");
+        if cache.can_fit(len) {
+            cache.insert(key.resource_id, version, data());
+        }
+    }
+
+    pub fn subscribe(&self) -> Receiver<(ResourcePathBuf, ChangeKind)> {
+        self.subscribers.subscribe()
+    }
+
+    /// Returns a copy of the loaders, to lock their backends without this lock.
+    pub fn mounts(&self) -> Mounts {
+        Mounts {
+            mounts: self.backends.iter()
+                .map(|(key, mount)| (key.clone(), mount.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns paths of all resources that have users.
+    pub fn resource_paths(&self) -> Vec<ResourcePathBuf> {
+        self.path_resource_ids.keys().cloned().collect()
+    }
+
+    /// Returns the sync point of changes that were not synced.
+    ///
+    /// Backends that are busy are not locked, they are checked on the next call.
     pub fn new_changes(&mut self) -> Option<InternalSyncPoint> {
         if let Some(instant) = self.outdated_at {
             return Some(InternalSyncPoint::Everything { time: instant });
         }
-        for (key, backend) in self.backends.iter_mut() {
-            if let Some(sync_point) = backend.new_changes() {
+        for (key, mount) in self.backends.iter_mut() {
+            let sync_point = try_lock_for_changes(&mount.backend).and_then(|mut b| b.new_changes());
+            if let Some(sync_point) = sync_point {
                 return Some(
                     InternalSyncPoint::Backend {
                         backend_hash: backend_hash(&key.id),
@@ -90,31 +426,52 @@ impl SharedResources {
         None
     }
 
-    pub fn notify_changes_synced(&mut self, sync_point: InternalSyncPoint) {
+    /// Outdates the resources and sends the changes to subscribers.
+    pub fn notify_changes(&mut self, changes: PathChanges, outdated_at: Instant) {
+        for path in changes.outdated {
+            if let Some(resource_id) = self.path_resource_ids.get(&path) {
+                if let Some(metadata) = self.resource_metadata.get_mut(*resource_id) {
+                    metadata.everyone_should_reload(outdated_at);
+                }
+            }
+        }
+        for (path, kind) in changes.sent {
+            self.subscribers.send(&path, kind);
+        }
+    }
+
+    /// Marks the changes as synced, and returns the backends that should be notified about it
+    /// after this lock is released.
+    pub fn notify_changes_synced(&mut self, sync_point: InternalSyncPoint) -> Vec<(SharedBackend, BackendSyncPoint)> {
         match sync_point {
-            InternalSyncPoint::Everything { time } => if self.outdated_at == Some(time) {
-                self.outdated_at = None;
+            InternalSyncPoint::Everything { time } => {
+                if self.outdated_at == Some(time) {
+                    self.outdated_at = None;
+                }
+                Vec::new()
             },
             InternalSyncPoint::Backend { backend_hash: bh, sync_point: sp } => {
-                for (key, backend) in self.backends.iter_mut() {
-                    if backend_hash(&key.id) == bh {
-                        backend.notify_changes_synced(sp);
-                    }
-                }
+                self.backends.iter()
+                    .filter(|(key, _)| backend_hash(&key.id) == bh)
+                    .map(|(_, mount)| (mount.backend.clone(), sp))
+                    .collect()
             }
         }
     }
 
     pub fn new_resource_user<P: AsRef<ResourcePath>>(&mut self, path: P) -> UserKey {
-        let clean_path_str: &ResourcePath = path.as_ref().as_clean_str().into();
-        let maybe_id = self.path_resource_ids.get(clean_path_str).cloned();
+        // users of paths outside of the root are kept under the cleaned path, `Resource`
+        // fails every operation on them before looking up the backends
+        let clean_path = resource_key(path.as_ref())
+            .unwrap_or_else(|_| ResourcePathBuf::from(String::from(path.as_ref().as_clean_str())));
+        let maybe_id = self.path_resource_ids.get(&clean_path).cloned();
         match maybe_id {
             Some(id) => self.append_resource_user(id),
             None => {
-                let mut metadata = ResourceMetadata::new(clean_path_str);
+                let mut metadata = ResourceMetadata::new(&clean_path);
                 let user_id = metadata.new_user();
                 let resource_id = self.resource_metadata.insert(metadata);
-                self.path_resource_ids.insert(ResourcePathBuf::from(clean_path_str), resource_id);
+                self.path_resource_ids.insert(clean_path, resource_id);
 
                 UserKey { resource_id, user_id }
             }
@@ -144,6 +501,9 @@ impl SharedResources {
         if let Some(false) = has_users {
             let metadata = self.resource_metadata.remove(key.resource_id);
             self.path_resource_ids.remove(&metadata.path);
+            self.cache.lock()
+                .expect("failed to lock cache")
+                .remove(key.resource_id);
         }
     }
 
@@ -157,84 +517,98 @@ impl SharedResources {
             .and_then(|path_metadata| path_metadata.get_user_metadata_mut(key.user_id))
     }
 
-    pub fn insert_loader<L: Backend + 'static>(&mut self, loader_id: &str, order: isize, backend: L) {
+    /// Inserts the loader prepared by `Mounts::new_loader`.
+    pub fn insert_loader(&mut self, loader: NewLoader) {
         let outdated_at = Instant::now();
+        let NewLoader { key, mount, changes } = loader;
+        for (path, kind) in changes {
+            self.subscribers.send(&path, kind);
+        }
+        // the new backend is not shared yet, so locking it here does not block
         for (path, resource_id) in self.path_resource_ids.iter() {
-            if backend.exists(&path) {
+            if mount.exists(&path) {
                 if let Some(metadata) = self.resource_metadata.get_mut(*resource_id) {
                     metadata.everyone_should_reload(outdated_at);
                 }
             }
         }
-        self.backends.insert(
-            LoaderKey { id: loader_id.into(), order },
-            Box::new(backend) as Box<Backend>,
-        );
+        self.backends.insert(key, mount);
         if self.path_resource_ids.len() > 0 {
             self.outdated_at = Some(outdated_at);
         }
     }
 
-    pub fn remove_loader(&mut self, loader_id: &str) {
-        let outdated_at = Instant::now();
-        let remove_keys: Vec<_> = self.backends.keys().filter(|k| k.id == loader_id).map(|k| k.clone()).collect();
-        for removed_key in remove_keys {
-            if let Some(removed_backend) = self.backends.remove(&removed_key) {
-                for (path, resource_id) in self.path_resource_ids.iter() {
-                    if removed_backend.exists(&path) {
-                        if let Some(metadata) = self.resource_metadata.get_mut(*resource_id) {
-                            metadata.everyone_should_reload(outdated_at);
-                        }
-                    }
-                }
-            }
+    pub fn set_write_policy(&mut self, loader_id: &str, write_policy: WritePolicy) {
+        for (_, mount) in self.backends.iter_mut().filter(|(k, _)| k.id == loader_id) {
+            mount.write_policy = write_policy;
         }
+    }
+
+    /// Removes the loader, and returns the removed mounts for `Mounts::removal_changes`.
+    pub fn remove_loader(&mut self, loader_id: &str, outdated_at: Instant) -> Mounts {
+        let remove_keys: Vec<_> = self.backends.keys().filter(|k| k.id == loader_id).map(|k| k.clone()).collect();
+        let removed = Mounts {
+            mounts: remove_keys.into_iter()
+                .filter_map(|key| self.backends.remove(&key).map(|mount| (key, mount)))
+                .collect(),
+        };
         if self.path_resource_ids.len() > 0 {
             self.outdated_at = Some(outdated_at);
         }
+        removed
     }
 
-    pub fn resource_backends(&mut self, key: UserKey) -> impl Iterator<Item=(&ResourcePath, Option<Instant>, &mut Box<Backend>)> {
+    pub fn resource_backends(&self, key: UserKey) -> ResourceBackends {
         let path_with_modification_time = self.resource_metadata.get(key.resource_id)
             .and_then(|m|
                 m.users.get(key.user_id)
-                    .map(|u| (m.path.as_ref(), u.outdated_at))
+                    .map(|u| (m.path.as_ref(), u.outdated_at, m.version))
             );
 
-        self.backends.iter_mut().rev()
-            .filter_map(move |(_, b)|
-                path_with_modification_time.map(move |(path, instant)|
-                    (path, instant, b)
-                )
-            )
-    }
-
-    pub fn get_resource_path_backend(&self, backend_id: &str, key: UserKey) -> Option<(&ResourcePath, Option<Instant>, &Box<Backend>)> {
-        let path_with_modification_time = self.resource_metadata.get(key.resource_id)
-            .and_then(|m|
-                m.users.get(key.user_id)
-                    .map(|u| (m.path.as_ref(), u.outdated_at))
-            );
-
-        if let (Some((path, modification_time)), Some((_, backend))) = (path_with_modification_time, self.backends.iter().filter(|(k, _)| &k.id == backend_id).next()) {
-            return Some((path, modification_time, backend));
+        match path_with_modification_time {
+            Some((path, modification_time, version)) => ResourceBackends {
+                path: path.into(),
+                modification_time,
+                version,
+                backends: self.backends.iter().rev()
+                    .filter_map(|(key, mount)|
+                        mount.backend_path(path)
+                            .map(|backend_path| ServingBackend {
+                                loader_id: key.id.clone(),
+                                mount_path: mount.path.clone(),
+                                path: backend_path.into(),
+                                write_policy: mount.write_policy,
+                                backend: mount.backend.clone(),
+                            })
+                    )
+                    .collect(),
+                stats: self.stats.clone(),
+            },
+            None => ResourceBackends {
+                path: ResourcePathBuf::from(String::new()),
+                modification_time: None,
+                version: 0,
+                backends: Vec::new(),
+                stats: None,
+            },
         }
+    }
 
-        None
+    pub fn resource_path(&self, key: UserKey) -> Option<&ResourcePath> {
+        self.resource_metadata.get(key.resource_id)
+            .map(|m| m.path.as_ref())
     }
 
-    pub fn get_resource_path_backend_containing_resource(&self, key: UserKey) -> Option<(&ResourcePath, Option<Instant>, &Box<Backend>)> {
+    pub fn get_resource_path_backend(&self, backend_id: &str, key: UserKey) -> Option<(&ResourcePath, Option<Instant>, WritePolicy, SharedBackend)> {
         let path_with_modification_time = self.resource_metadata.get(key.resource_id)
             .and_then(|m|
                 m.users.get(key.user_id)
                     .map(|u| (m.path.as_ref(), u.outdated_at))
             );
 
-        if let Some((path, modification_time)) = path_with_modification_time {
-            for backend in self.backends.values().rev() {
-                if backend.exists(path) {
-                    return Some((path, modification_time, backend));
-                }
+        if let (Some((path, modification_time)), Some((_, mount))) = (path_with_modification_time, self.backends.iter().filter(|(k, _)| &k.id == backend_id).next()) {
+            if let Some(path) = mount.backend_path(path) {
+                return Some((path, modification_time, mount.write_policy, mount.backend.clone()));
             }
         }
 
@@ -249,9 +623,27 @@ impl SharedResources {
         }
     }
 
+    /// Outdates all proxies of the path, and sends the change to subscribers.
+    ///
+    /// Used when the resource was deleted or renamed, and it may still be served by a loader
+    /// with a lower order, see `Mounts::change_after_removal`.
+    pub fn notify_path_changed(&mut self, path: &ResourcePath, kind: ChangeKind, modified_time: Instant) {
+        let path = &match resource_key(path) {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        if let Some(resource_id) = self.path_resource_ids.get(path) {
+            if let Some(metadata) = self.resource_metadata.get_mut(*resource_id) {
+                metadata.everyone_should_reload(modified_time);
+            }
+        }
+        self.subscribers.send(path, kind);
+    }
+
     pub fn notify_did_write(&mut self, key: UserKey, modified_time: Instant) {
         if let Some(metadata) = self.resource_metadata.get_mut(key.resource_id) {
-            metadata.everyone_should_reload_except(key.user_id, modified_time)
+            metadata.everyone_should_reload_except(key.user_id, modified_time);
+            self.subscribers.send(&metadata.path, ChangeKind::Modified);
         }
     }
 }
\ No newline at end of file
@@ -17,6 +17,8 @@ pub struct ResourceUserMetadata {
 pub struct ResourceMetadata {
     pub path: ResourcePathBuf,
     pub users: Slab<ResourceUserMetadata>,
+    /// Incremented every time the resource is outdated, so that cached contents can be checked.
+    pub version: u64,
 }
 
 impl ResourceMetadata {
@@ -24,6 +26,7 @@ impl ResourceMetadata {
         ResourceMetadata {
             path: ResourcePathBuf::from(path),
             users: Slab::with_capacity(2),
+            version: 0,
         }
     }
 
@@ -53,12 +56,14 @@ impl ResourceMetadata {
     }
 
     pub fn everyone_should_reload_except(&mut self, id: usize, outdated_at: Instant) {
+        self.version += 1;
         for (user_id, user) in self.users.iter_mut() {
             user.outdated_at = if user_id != id { Some(outdated_at) } else { None };
         }
     }
 
     pub fn everyone_should_reload(&mut self, outdated_at: Instant) {
+        self.version += 1;
         for (_, user) in self.users.iter_mut() {
             user.outdated_at = Some(outdated_at);
         }
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use {ResourcePath, ResourcePathBuf};
+
+/// Reads and cache use of a resource or a loader.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AccessCounts {
+    /// Reads that were served by a backend.
+    pub reads: u64,
+    /// Bytes returned by backends, streams are read on demand and are not counted.
+    pub bytes: u64,
+    /// Time spent reading from backends.
+    pub read_time: Duration,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl AccessCounts {
+    fn add_read(&mut self, bytes: u64, time: Duration) {
+        self.reads += 1;
+        self.bytes += bytes;
+        self.read_time += time;
+    }
+}
+
+/// Snapshot of the statistics recorded by `Resources::with_access_stats`.
+///
+/// Cache hits and misses are only counted for paths, because cached contents are not read from a loader.
+/// They are not counted at all while the cache budget is 0.
+/// The `Display` output lists loaders and paths that were read the most first.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AccessStats {
+    pub paths: BTreeMap<ResourcePathBuf, AccessCounts>,
+    pub loaders: BTreeMap<String, AccessCounts>,
+}
+
+impl fmt::Display for AccessStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        writeln!(f, "{:>8} {:>12} {:>10} {:>8} {:>8}  loader", "reads", "bytes", "ms", "hits", "misses")?;
+        write_rows(f, self.loaders.iter().map(|(id, counts)| (&id[..], counts)))?;
+        writeln!(f, "{:>8} {:>12} {:>10} {:>8} {:>8}  path", "reads", "bytes", "ms", "hits", "misses")?;
+        write_rows(f, self.paths.iter().map(|(path, counts)| (path.as_clean_str(), counts)))
+    }
+}
+
+fn write_rows<'a, I>(f: &mut fmt::Formatter, rows: I) -> Result<(), fmt::Error>
+    where I: Iterator<Item = (&'a str, &'a AccessCounts)>
+{
+    let mut rows: Vec<_> = rows.collect();
+    rows.sort_by(|a, b| b.1.reads.cmp(&a.1.reads).then(b.1.bytes.cmp(&a.1.bytes)));
+
+    for (name, counts) in rows {
+        let ms = counts.read_time.as_secs() as f64 * 1000.0 + counts.read_time.subsec_nanos() as f64 / 1_000_000.0;
+        writeln!(
+            f, "{:>8} {:>12} {:>10.3} {:>8} {:>8}  {}",
+            counts.reads, counts.bytes, ms, counts.cache_hits, counts.cache_misses, name
+        )?;
+    }
+    Ok(())
+}
+
+/// Records statistics of reads that may run on any thread.
+pub struct StatsRecorder {
+    stats: Mutex<AccessStats>,
+}
+
+impl StatsRecorder {
+    pub fn new() -> StatsRecorder {
+        StatsRecorder {
+            stats: Mutex::new(AccessStats::default()),
+        }
+    }
+
+    pub fn record_read(&self, path: &ResourcePath, loader_id: &str, bytes: u64, time: Duration) {
+        let mut stats = self.stats.lock().expect("failed to lock stats");
+        stats.paths.entry(path.into()).or_insert_with(AccessCounts::default)
+            .add_read(bytes, time);
+        stats.loaders.entry(loader_id.into()).or_insert_with(AccessCounts::default)
+            .add_read(bytes, time);
+    }
+
+    pub fn record_cache_use(&self, path: &ResourcePath, hit: bool) {
+        let mut stats = self.stats.lock().expect("failed to lock stats");
+        let counts = stats.paths.entry(path.into()).or_insert_with(AccessCounts::default);
+        if hit {
+            counts.cache_hits += 1;
+        } else {
+            counts.cache_misses += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> AccessStats {
+        self.stats.lock().expect("failed to lock stats").clone()
+    }
+
+    pub fn reset(&self) {
+        *self.stats.lock().expect("failed to lock stats") = AccessStats::default();
+    }
+}
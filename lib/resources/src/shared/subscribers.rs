@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Sender, Receiver};
+use {ResourcePath, ResourcePathBuf};
+
+/// The way the resource at the path has changed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ChangeKind {
+    /// The resource was written, changed outside of this process, or appeared in a loader.
+    Modified,
+    /// No loader contains the resource anymore.
+    Removed,
+    /// A loader with a higher order now provides the resource.
+    Overridden,
+}
+
+/// Senders of all `Resources::subscribe` channels.
+///
+/// Channels with dropped receivers are removed on the next change.
+pub struct Subscribers {
+    senders: Mutex<Vec<Sender<(ResourcePathBuf, ChangeKind)>>>,
+}
+
+impl Subscribers {
+    pub fn new() -> Subscribers {
+        Subscribers {
+            senders: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn subscribe(&self) -> Receiver<(ResourcePathBuf, ChangeKind)> {
+        let (sender, receiver) = mpsc::channel();
+        self.senders.lock()
+            .expect("failed to lock subscribers")
+            .push(sender);
+        receiver
+    }
+
+    pub fn send(&self, path: &ResourcePath, kind: ChangeKind) {
+        let mut senders = self.senders.lock()
+            .expect("failed to lock subscribers");
+        if senders.is_empty() {
+            return;
+        }
+        senders.retain(|sender| sender.send((ResourcePathBuf::from(path), kind)).is_ok());
+    }
+}
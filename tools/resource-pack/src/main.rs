@@ -0,0 +1,142 @@
+extern crate failure;
+extern crate resources;
+
+use failure::err_msg;
+use resources::backend::{Backend, FileSystem, Pack, PackWriter, PackCompression};
+use std::env;
+use std::fs;
+use std::io;
+
+const USAGE: &str = "Usage:
+    resource-pack pack <assets dir> <pack file> [--compress] [--level <0-10>]
+    resource-pack list <pack file>
+    resource-pack extract <pack file> <output dir>
+    resource-pack verify <pack file>";
+
+const DEFAULT_LEVEL: u8 = 6;
+
+fn main() {
+    if let Err(e) = run() {
+        print_error(&e);
+        ::std::process::exit(1);
+    }
+}
+
+/// Prints the error and its causes to stderr, so that the output of `list` and `extract`
+/// can be piped.
+fn print_error(e: &failure::Error) {
+    for (i, cause) in e.iter_chain().enumerate() {
+        if i > 0 {
+            eprint!("   caused by: ");
+        }
+        eprintln!("{}", cause);
+    }
+}
+
+fn run() -> Result<(), failure::Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+
+    match (args.get(0).cloned(), args.len()) {
+        (Some("pack"), len) if len >= 3 => pack(args[1], args[2], compression(&args[3..])?),
+        (Some("list"), 2) => list(args[1]),
+        (Some("extract"), 3) => extract(args[1], args[2]),
+        (Some("verify"), 2) => verify(args[1]),
+        _ => Err(err_msg(USAGE)),
+    }
+}
+
+fn compression(options: &[&str]) -> Result<PackCompression, failure::Error> {
+    let mut compress = false;
+    let mut level = DEFAULT_LEVEL;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--compress" => compress = true,
+            "--level" => {
+                level = options.next()
+                    .and_then(|l| l.parse().ok())
+                    .filter(|l| *l <= 10)
+                    .ok_or_else(|| err_msg("--level expects a number from 0 to 10"))?;
+                compress = true;
+            },
+            other => return Err(err_msg(format!("Unknown option {}\n{}", other, USAGE))),
+        }
+    }
+
+    Ok(if compress { PackCompression::Zlib { level } } else { PackCompression::None })
+}
+
+fn pack(assets_dir: &str, pack_file: &str, compression: PackCompression) -> Result<(), failure::Error> {
+    let assets = FileSystem::from_path(assets_dir);
+
+    let mut paths = assets.list("".into());
+    paths.sort();
+
+    let mut writer = PackWriter::new(io::BufWriter::new(fs::File::create(pack_file)?))?;
+    let mut total_len = 0;
+    let mut total_stored_len = 0;
+
+    for path in paths {
+        let data = assets.read_vec(&path)?;
+        let entry = writer.add(&path, &data, compression)?;
+        total_len += entry.len;
+        total_stored_len += entry.stored_len;
+        println!("{:>10} {:>10} {}", entry.len, entry.stored_len, path);
+    }
+
+    writer.finish()?;
+    println!("Packed {} bytes into {} bytes", total_len, total_stored_len);
+
+    Ok(())
+}
+
+fn list(pack_file: &str) -> Result<(), failure::Error> {
+    let pack = Pack::from_path(pack_file)?;
+
+    println!("{:>10} {:>10} {:>16} {}", "length", "stored", "hash", "path");
+    for (path, entry) in pack.entries() {
+        println!(
+            "{:>10} {:>10} {:016x} {}{}",
+            entry.len, entry.stored_len, entry.hash, path,
+            if entry.compressed { " (zlib)" } else { "" }
+        );
+    }
+
+    Ok(())
+}
+
+fn extract(pack_file: &str, output_dir: &str) -> Result<(), failure::Error> {
+    let pack = Pack::from_path(pack_file)?;
+    let mut output = FileSystem::from_path(output_dir).with_write();
+
+    for (path, _) in pack.entries() {
+        let data = pack.read_vec(path)?;
+        output.write(path, &data)?;
+        println!("{}", path);
+    }
+
+    Ok(())
+}
+
+fn verify(pack_file: &str) -> Result<(), failure::Error> {
+    let pack = Pack::from_path(pack_file)?;
+
+    let entries = pack.entries();
+
+    let mut failed = 0;
+    for &(path, _) in &entries {
+        if let Err(e) = pack.verify(path) {
+            eprintln!("FAILED {}: {}", path, e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(err_msg(format!("{} of {} entries failed verification", failed, entries.len())));
+    }
+
+    println!("All {} entries are valid", entries.len());
+    Ok(())
+}